@@ -0,0 +1,865 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    time::Duration,
+};
+
+use anymap::AnyMap;
+use winit::{
+    event::{DeviceEvent, DeviceId, VirtualKeyCode, WindowEvent},
+    event_loop::ControlFlow,
+};
+
+use super::{
+    capture::RgbaImage,
+    config::EngineConfig,
+    context::Context,
+    error::EngineError,
+    framework::{self, FramerateLimit, Framework},
+    input::ActionMap,
+    plugin::Plugin,
+    stats::FrameStats,
+    target::RenderTargets,
+    time::TickRate,
+    window::WindowId,
+};
+
+/// When the systems of an [`App`] run. Within a frame, stages run in the order they're declared in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// Once, after the device has been created.
+    Startup,
+    /// At the app's [`TickRate`], before rendering.
+    FixedUpdate,
+    /// Once per frame, before rendering.
+    Update,
+    PreRender,
+    Render,
+    PostRender,
+    /// Before the first frame after the surface was resized.
+    Resize,
+    /// Once, when the event loop exits.
+    Shutdown,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 8] = [
+        Stage::Startup,
+        Stage::FixedUpdate,
+        Stage::Update,
+        Stage::PreRender,
+        Stage::Render,
+        Stage::PostRender,
+        Stage::Resize,
+        Stage::Shutdown,
+    ];
+}
+
+/// Values shared between systems, at most one of every type.
+pub struct Resources(AnyMap);
+
+impl Default for Resources {
+    fn default() -> Self {
+        Self(AnyMap::new())
+    }
+}
+
+impl Resources {
+    /// Inserts `resource`, returning the one of the same type it replaced.
+    pub fn insert<T: 'static>(&mut self, resource: T) -> Option<T> {
+        self.0.insert(resource)
+    }
+
+    pub fn remove<T: 'static>(&mut self) -> Option<T> {
+        self.0.remove::<T>()
+    }
+
+    pub fn get<T: 'static>(&self) -> Option<&T> {
+        self.0.get::<T>()
+    }
+
+    pub fn get_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.0.get_mut::<T>()
+    }
+
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.0.contains::<T>()
+    }
+}
+
+/// The device and the surface state, available to systems outside of [`Stage::FixedUpdate`] and
+/// [`Stage::Shutdown`].
+#[derive(Clone, Copy)]
+pub struct Gpu<'a> {
+    pub device: &'a wgpu::Device,
+    pub queue: &'a wgpu::Queue,
+    pub config: &'a wgpu::SurfaceConfiguration,
+    pub targets: &'a RenderTargets,
+}
+
+/// The frame being rendered, available to systems in [`Stage::PreRender`], [`Stage::Render`] and
+/// [`Stage::PostRender`].
+pub struct Frame<'a> {
+    pub encoder: &'a mut wgpu::CommandEncoder,
+    pub view: &'a wgpu::TextureView,
+}
+
+/// Everything a system has access to.
+///
+/// The fields are public so a system can borrow several of them at once.
+pub struct World<'a> {
+    pub resources: &'a mut Resources,
+    pub ctx: &'a mut Context,
+    /// Only set during [`Stage::Startup`].
+    pub adapter: Option<&'a wgpu::Adapter>,
+    pub gpu: Option<Gpu<'a>>,
+    pub frame: Option<Frame<'a>>,
+    /// The tick length during [`Stage::FixedUpdate`], and zero in every other stage.
+    pub fixed_dt: Duration,
+}
+
+impl<'a> World<'a> {
+    /// The device and surface state, which isn't available in [`Stage::FixedUpdate`] and
+    /// [`Stage::Shutdown`].
+    pub fn gpu(&self) -> Option<Gpu<'a>> {
+        self.gpu
+    }
+}
+
+/// Turns what a system returns into a result, so systems can return either `()` or `anyhow::Result<()>`.
+pub trait SystemResult {
+    fn into_result(self) -> anyhow::Result<()>;
+}
+
+impl SystemResult for () {
+    fn into_result(self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+impl SystemResult for anyhow::Result<()> {
+    fn into_result(self) -> anyhow::Result<()> {
+        self
+    }
+}
+
+type SystemFn = Box<dyn FnMut(&mut World<'_>) -> anyhow::Result<()>>;
+
+/// A named function run in a [`Stage`], optionally ordered relative to other systems by name.
+///
+/// Failing [`Stage::Startup`] systems stop the app from starting, failures in every other stage
+/// are logged.
+pub struct System {
+    name: String,
+    before: Vec<String>,
+    after: Vec<String>,
+    run: SystemFn,
+}
+
+impl System {
+    pub fn new<R: SystemResult>(
+        name: impl Into<String>,
+        mut run: impl FnMut(&mut World<'_>) -> R + 'static,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            before: Vec::new(),
+            after: Vec::new(),
+            run: Box::new(move |world| run(world).into_result()),
+        }
+    }
+
+    /// Runs this system before every system in the same stage called `name`.
+    pub fn before(mut self, name: impl Into<String>) -> Self {
+        self.before.push(name.into());
+        self
+    }
+
+    /// Runs this system after every system in the same stage called `name`.
+    pub fn after(mut self, name: impl Into<String>) -> Self {
+        self.after.push(name.into());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+type RenderWindowHook = fn(
+    &mut Resources,
+    &mut Context,
+    WindowId,
+    &mut wgpu::CommandEncoder,
+    &wgpu::TextureView,
+    &RenderTargets,
+    &wgpu::Device,
+    &wgpu::Queue,
+);
+
+type ResizeWindowHook = fn(
+    &mut Resources,
+    WindowId,
+    &wgpu::SurfaceConfiguration,
+    &RenderTargets,
+    &wgpu::Device,
+    &wgpu::Queue,
+);
+
+/// The [`Framework`] hooks that don't map onto a [`Stage`], forwarded by a
+/// [`FrameworkPlugin`](super::plugin::FrameworkPlugin) to the framework in its resources.
+///
+/// Hooks that return something return `None` while the framework isn't in the resources.
+#[derive(Clone, Copy)]
+pub(crate) struct FrameworkHooks {
+    pub tick_rate: fn(&Resources) -> Option<TickRate>,
+    pub action_map: fn(&Resources) -> Option<ActionMap>,
+    /// Only called for windows other than [`WindowId::MAIN`], which renders in [`Stage::Render`].
+    pub render_window: RenderWindowHook,
+    /// Only called for windows other than [`WindowId::MAIN`], which resizes in [`Stage::Resize`].
+    pub resize_window: ResizeWindowHook,
+    pub window_closed: fn(&mut Resources, &mut Context, WindowId),
+    pub maximum_framerate: fn(&Resources) -> Option<FramerateLimit>,
+    pub background_framerate: fn(&Resources) -> Option<FramerateLimit>,
+    pub window_title: fn(&Resources, &FrameStats) -> Option<String>,
+    pub screenshot_key: fn(&Resources) -> Option<VirtualKeyCode>,
+    pub wants_capture: fn(&mut Resources) -> bool,
+    pub on_capture: fn(&mut Resources, RgbaImage),
+    pub close_requested: fn(&mut Resources, &mut Context) -> Option<bool>,
+    pub focus_changed: fn(&mut Resources, &mut Context, bool),
+    pub suspended: fn(&mut Resources, &mut Context),
+    pub resumed: fn(&mut Resources, &mut Context),
+    pub input: fn(&mut Resources, DeviceId, DeviceEvent),
+    pub on_event: fn(&mut Resources, WindowEvent, &mut ControlFlow),
+}
+
+/// Builds an application out of [`Plugin`]s, as an alternative to a single [`Framework`].
+///
+/// ```ignore
+/// let mut app = App::new(EngineConfig::load_or_default("engine.toml")?);
+/// app.add_plugin(CameraPlugin::new(camera))
+///     .add_plugin(BundlesPlugin::<StaticColorMaterial>::default())
+///     .add_system(Stage::Render, System::new("draw", draw));
+/// app.run()?;
+/// ```
+pub struct App {
+    engine_config: EngineConfig,
+    resources: Resources,
+    stages: HashMap<Stage, Vec<System>>,
+    tick_rate: Option<TickRate>,
+    maximum_framerate: Option<FramerateLimit>,
+    background_framerate: Option<FramerateLimit>,
+    action_map: ActionMap,
+    framework_hooks: Option<FrameworkHooks>,
+}
+
+impl App {
+    pub fn new(engine_config: EngineConfig) -> Self {
+        Self {
+            engine_config,
+            resources: Resources::default(),
+            stages: HashMap::new(),
+            tick_rate: None,
+            maximum_framerate: None,
+            background_framerate: None,
+            action_map: ActionMap::default(),
+            framework_hooks: None,
+        }
+    }
+
+    pub fn add_plugin(&mut self, plugin: impl Plugin) -> &mut Self {
+        plugin.build(self);
+        self
+    }
+
+    /// Adds `system` to `stage`. Systems without an order between them run in the order they were added.
+    pub fn add_system(&mut self, stage: Stage, system: System) -> &mut Self {
+        self.stages.entry(stage).or_default().push(system);
+        self
+    }
+
+    pub fn insert_resource<T: 'static>(&mut self, resource: T) -> &mut Self {
+        self.resources.insert(resource);
+        self
+    }
+
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    pub fn resources_mut(&mut self) -> &mut Resources {
+        &mut self.resources
+    }
+
+    pub fn engine_config(&self) -> &EngineConfig {
+        &self.engine_config
+    }
+
+    /// Sets the tick rate, over that of a [`FrameworkPlugin`](super::plugin::FrameworkPlugin)'s
+    /// framework.
+    pub fn set_tick_rate(&mut self, tick_rate: TickRate) -> &mut Self {
+        self.tick_rate = Some(tick_rate);
+        self
+    }
+
+    /// Limits the framerate, over the limits of a
+    /// [`FrameworkPlugin`](super::plugin::FrameworkPlugin)'s framework. Unlimited by default.
+    pub fn set_maximum_framerate(&mut self, limit: FramerateLimit) -> &mut Self {
+        self.maximum_framerate = Some(limit);
+        self
+    }
+
+    /// Limits the framerate while the app is in the background, by default the maximum framerate
+    /// applies there too. See [`Framework::background_framerate`].
+    pub fn set_background_framerate(&mut self, limit: FramerateLimit) -> &mut Self {
+        self.background_framerate = Some(limit);
+        self
+    }
+
+    /// Actions and axes bound here replace those of the same name a
+    /// [`FrameworkPlugin`](super::plugin::FrameworkPlugin)'s framework binds.
+    pub fn actions_mut(&mut self) -> &mut ActionMap {
+        &mut self.action_map
+    }
+
+    /// Only one framework gets the hooks, the one whose plugin was added last.
+    pub(crate) fn set_framework_hooks(&mut self, hooks: FrameworkHooks) -> &mut Self {
+        if self.framework_hooks.replace(hooks).is_some() {
+            log::warn!("an app only forwards input and events to the last framework added");
+        }
+
+        self
+    }
+
+    /// Opens a window and runs the app in it, like [`framework::run`].
+    pub fn run(self) -> Result<(), EngineError> {
+        let (engine_config, mut runner) = self.into_runner()?;
+
+        framework::run_with(
+            engine_config,
+            move |config, targets, adapter, device, queue, ctx| {
+                runner.config = Some(config.clone());
+                runner.run_stage(
+                    Stage::Startup,
+                    ctx,
+                    Some(adapter),
+                    Some(Gpu {
+                        device,
+                        queue,
+                        config,
+                        targets,
+                    }),
+                    None,
+                    Duration::ZERO,
+                )?;
+
+                Ok(runner)
+            },
+        )
+    }
+
+    fn into_runner(self) -> Result<(EngineConfig, AppRunner), EngineError> {
+        let mut stages = HashMap::new();
+
+        for (stage, systems) in self.stages {
+            stages.insert(stage, sort_systems(stage, systems)?);
+        }
+
+        let runner = AppRunner {
+            stages,
+            resources: self.resources,
+            config: None,
+            resized: false,
+            tick_rate: self.tick_rate,
+            maximum_framerate: self.maximum_framerate,
+            background_framerate: self.background_framerate,
+            action_map: self.action_map,
+            framework_hooks: self.framework_hooks,
+        };
+
+        Ok((self.engine_config, runner))
+    }
+}
+
+/// Drives the stages of an [`App`] from the [`Framework`] hooks.
+pub(crate) struct AppRunner {
+    stages: HashMap<Stage, Vec<System>>,
+    resources: Resources,
+    config: Option<wgpu::SurfaceConfiguration>,
+    resized: bool,
+    tick_rate: Option<TickRate>,
+    maximum_framerate: Option<FramerateLimit>,
+    background_framerate: Option<FramerateLimit>,
+    action_map: ActionMap,
+    framework_hooks: Option<FrameworkHooks>,
+}
+
+impl AppRunner {
+    fn run_stage(
+        &mut self,
+        stage: Stage,
+        ctx: &mut Context,
+        adapter: Option<&wgpu::Adapter>,
+        gpu: Option<Gpu>,
+        mut frame: Option<(&mut wgpu::CommandEncoder, &wgpu::TextureView)>,
+        fixed_dt: Duration,
+    ) -> anyhow::Result<()> {
+        let Some(systems) = self.stages.get_mut(&stage) else {
+            return Ok(());
+        };
+
+        for system in systems {
+            let mut world = World {
+                resources: &mut self.resources,
+                ctx: &mut *ctx,
+                adapter,
+                gpu,
+                frame: frame
+                    .as_mut()
+                    .map(|(encoder, view)| Frame { encoder, view }),
+                fixed_dt,
+            };
+
+            if let Err(err) = (system.run)(&mut world) {
+                let err = err.context(format!("system {:?} failed in {stage:?}", system.name));
+
+                if stage == Stage::Startup {
+                    return Err(err);
+                }
+
+                log::error!("{err:?}");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Framework for AppRunner {
+    fn init(
+        _config: &wgpu::SurfaceConfiguration,
+        _targets: &RenderTargets,
+        _adapter: &wgpu::Adapter,
+        _device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+    ) -> anyhow::Result<Self> {
+        anyhow::bail!("apps are started through App::run")
+    }
+
+    fn resize(
+        &mut self,
+        config: &wgpu::SurfaceConfiguration,
+        _targets: &RenderTargets,
+        _device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+    ) {
+        // resize systems need the context, which is only around while rendering.
+        self.config = Some(config.clone());
+        self.resized = true;
+    }
+
+    fn update(&mut self, ctx: &mut Context, fixed_dt: Duration) {
+        let _ = self.run_stage(Stage::FixedUpdate, ctx, None, None, None, fixed_dt);
+    }
+
+    fn tick_rate(&self) -> TickRate {
+        self.tick_rate
+            .or_else(|| {
+                self.framework_hooks
+                    .and_then(|hooks| (hooks.tick_rate)(&self.resources))
+            })
+            .unwrap_or_default()
+    }
+
+    fn render(
+        &mut self,
+        ctx: &mut Context,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        targets: &RenderTargets,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        let config = self
+            .config
+            .take()
+            .expect("the surface config is set during startup");
+
+        let gpu = Gpu {
+            device,
+            queue,
+            config: &config,
+            targets,
+        };
+
+        if std::mem::take(&mut self.resized) {
+            let _ = self.run_stage(Stage::Resize, ctx, None, Some(gpu), None, Duration::ZERO);
+        }
+
+        let _ = self.run_stage(Stage::Update, ctx, None, Some(gpu), None, Duration::ZERO);
+
+        for stage in [Stage::PreRender, Stage::Render, Stage::PostRender] {
+            let frame = Some((&mut *encoder, view));
+            let _ = self.run_stage(stage, ctx, None, Some(gpu), frame, Duration::ZERO);
+        }
+
+        self.config = Some(config);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_window(
+        &mut self,
+        ctx: &mut Context,
+        id: WindowId,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        targets: &RenderTargets,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        if id == WindowId::MAIN {
+            self.render(ctx, encoder, view, targets, device, queue);
+        } else if let Some(hooks) = self.framework_hooks {
+            (hooks.render_window)(
+                &mut self.resources,
+                ctx,
+                id,
+                encoder,
+                view,
+                targets,
+                device,
+                queue,
+            );
+        }
+    }
+
+    fn resize_window(
+        &mut self,
+        id: WindowId,
+        config: &wgpu::SurfaceConfiguration,
+        targets: &RenderTargets,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        if id == WindowId::MAIN {
+            self.resize(config, targets, device, queue);
+        } else if let Some(hooks) = self.framework_hooks {
+            (hooks.resize_window)(&mut self.resources, id, config, targets, device, queue);
+        }
+    }
+
+    fn window_closed(&mut self, ctx: &mut Context, id: WindowId) {
+        if let Some(hooks) = self.framework_hooks {
+            (hooks.window_closed)(&mut self.resources, ctx, id);
+        }
+    }
+
+    fn maximum_framerate(&self) -> FramerateLimit {
+        self.maximum_framerate
+            .or_else(|| {
+                self.framework_hooks
+                    .and_then(|hooks| (hooks.maximum_framerate)(&self.resources))
+            })
+            .unwrap_or(FramerateLimit::Unlimited)
+    }
+
+    fn background_framerate(&self) -> FramerateLimit {
+        // a limit set on the app replaces both of the framework's.
+        match (self.background_framerate, self.maximum_framerate) {
+            (Some(limit), _) | (None, Some(limit)) => limit,
+            (None, None) => self
+                .framework_hooks
+                .and_then(|hooks| (hooks.background_framerate)(&self.resources))
+                .unwrap_or(FramerateLimit::Unlimited),
+        }
+    }
+
+    fn window_title(&self, stats: &FrameStats) -> Option<String> {
+        self.framework_hooks
+            .and_then(|hooks| (hooks.window_title)(&self.resources, stats))
+    }
+
+    fn screenshot_key(&self) -> Option<VirtualKeyCode> {
+        self.framework_hooks
+            .and_then(|hooks| (hooks.screenshot_key)(&self.resources))
+    }
+
+    fn wants_capture(&mut self) -> bool {
+        self.framework_hooks
+            .is_some_and(|hooks| (hooks.wants_capture)(&mut self.resources))
+    }
+
+    fn on_capture(&mut self, image: RgbaImage) {
+        match self.framework_hooks {
+            Some(hooks) => (hooks.on_capture)(&mut self.resources, image),
+            None => framework::save_screenshot(image),
+        }
+    }
+
+    fn close_requested(&mut self, ctx: &mut Context) -> bool {
+        self.framework_hooks
+            .and_then(|hooks| (hooks.close_requested)(&mut self.resources, ctx))
+            .unwrap_or(true)
+    }
+
+    fn focus_changed(&mut self, ctx: &mut Context, focused: bool) {
+        if let Some(hooks) = self.framework_hooks {
+            (hooks.focus_changed)(&mut self.resources, ctx, focused);
+        }
+    }
+
+    fn suspended(&mut self, ctx: &mut Context) {
+        if let Some(hooks) = self.framework_hooks {
+            (hooks.suspended)(&mut self.resources, ctx);
+        }
+    }
+
+    fn resumed(&mut self, ctx: &mut Context) {
+        if let Some(hooks) = self.framework_hooks {
+            (hooks.resumed)(&mut self.resources, ctx);
+        }
+    }
+
+    fn action_map(&self) -> ActionMap {
+        let mut actions = self
+            .framework_hooks
+            .and_then(|hooks| (hooks.action_map)(&self.resources))
+            .unwrap_or_default();
+
+        actions.actions.extend(self.action_map.actions.clone());
+        actions.axes.extend(self.action_map.axes.clone());
+        actions
+    }
+
+    fn input(&mut self, device_id: DeviceId, event: DeviceEvent) {
+        if let Some(hooks) = self.framework_hooks {
+            (hooks.input)(&mut self.resources, device_id, event);
+        }
+    }
+
+    fn on_event(&mut self, event: WindowEvent, control_flow: &mut ControlFlow) {
+        if let Some(hooks) = self.framework_hooks {
+            (hooks.on_event)(&mut self.resources, event, control_flow);
+        }
+    }
+
+    fn shutdown(&mut self, ctx: &mut Context) {
+        let _ = self.run_stage(Stage::Shutdown, ctx, None, None, None, Duration::ZERO);
+    }
+}
+
+/// Orders the systems of a stage so every `before`/`after` constraint holds, keeping the order
+/// they were added in wherever there is no constraint.
+fn sort_systems(stage: Stage, systems: Vec<System>) -> Result<Vec<System>, EngineError> {
+    let indices_of = |name: &str| {
+        systems
+            .iter()
+            .enumerate()
+            .filter(move |(_, system)| system.name == name)
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>()
+    };
+
+    let mut edges = vec![Vec::new(); systems.len()];
+    let mut incoming = vec![0usize; systems.len()];
+
+    for (idx, system) in systems.iter().enumerate() {
+        let before = system.before.iter().map(|name| (name, true));
+        let after = system.after.iter().map(|name| (name, false));
+
+        for (name, is_before) in before.chain(after) {
+            let others = indices_of(name);
+
+            if others.is_empty() {
+                log::warn!(
+                    "system {:?} is ordered relative to {name:?}, which isn't in {stage:?}",
+                    system.name
+                );
+            }
+
+            for other in others {
+                let (from, to) = match is_before {
+                    true => (idx, other),
+                    false => (other, idx),
+                };
+
+                edges[from].push(to);
+                incoming[to] += 1;
+            }
+        }
+    }
+
+    let mut ready = (0..systems.len())
+        .filter(|idx| incoming[*idx] == 0)
+        .collect::<BTreeSet<_>>();
+    let mut order = Vec::with_capacity(systems.len());
+
+    while let Some(idx) = ready.iter().next().copied() {
+        ready.remove(&idx);
+        order.push(idx);
+
+        for &to in &edges[idx] {
+            incoming[to] -= 1;
+
+            if incoming[to] == 0 {
+                ready.insert(to);
+            }
+        }
+    }
+
+    if order.len() != systems.len() {
+        let cycle = systems
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !order.contains(idx))
+            .map(|(_, system)| system.name.clone())
+            .collect();
+
+        return Err(EngineError::SystemCycle(stage, cycle));
+    }
+
+    let mut systems = systems.into_iter().map(Some).collect::<Vec<_>>();
+
+    Ok(order
+        .into_iter()
+        .filter_map(|idx| systems[idx].take())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{plugin::FrameworkPlugin, time::Time};
+
+    fn names(systems: &[System]) -> Vec<&str> {
+        systems.iter().map(System::name).collect()
+    }
+
+    #[test]
+    fn sorts_by_before_and_after() {
+        let systems = vec![
+            System::new("draw", |_: &mut World| {}).after("camera"),
+            System::new("ui", |_: &mut World| {}),
+            System::new("camera", |_: &mut World| {}),
+            System::new("clear", |_: &mut World| {}).before("draw"),
+        ];
+
+        let sorted = sort_systems(Stage::Render, systems).unwrap();
+        assert_eq!(names(&sorted), ["ui", "camera", "clear", "draw"]);
+    }
+
+    #[test]
+    fn keeps_the_added_order_without_constraints() {
+        let systems = vec![
+            System::new("a", |_: &mut World| {}),
+            System::new("b", |_: &mut World| {}),
+            System::new("c", |_: &mut World| {}).after("missing"),
+        ];
+
+        let sorted = sort_systems(Stage::Update, systems).unwrap();
+        assert_eq!(names(&sorted), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn rejects_cycles() {
+        let systems = vec![
+            System::new("free", |_: &mut World| {}),
+            System::new("a", |_: &mut World| {}).before("b"),
+            System::new("b", |_: &mut World| {}).before("c"),
+            System::new("c", |_: &mut World| {}).before("a"),
+        ];
+
+        match sort_systems(Stage::Update, systems) {
+            Err(EngineError::SystemCycle(Stage::Update, cycle)) => {
+                assert_eq!(cycle, ["a", "b", "c"])
+            }
+            _ => panic!("the cycle wasn't rejected"),
+        }
+    }
+
+    struct Stubborn;
+
+    impl Framework for Stubborn {
+        fn init(
+            _config: &wgpu::SurfaceConfiguration,
+            _targets: &RenderTargets,
+            _adapter: &wgpu::Adapter,
+            _device: &wgpu::Device,
+            _queue: &wgpu::Queue,
+        ) -> anyhow::Result<Self> {
+            Ok(Self)
+        }
+
+        fn render(
+            &mut self,
+            _ctx: &mut Context,
+            _encoder: &mut wgpu::CommandEncoder,
+            _view: &wgpu::TextureView,
+            _targets: &RenderTargets,
+            _device: &wgpu::Device,
+            _queue: &wgpu::Queue,
+        ) {
+        }
+
+        fn close_requested(&mut self, _ctx: &mut Context) -> bool {
+            false
+        }
+
+        fn window_title(&self, _stats: &FrameStats) -> Option<String> {
+            Some("stubborn".to_string())
+        }
+
+        fn maximum_framerate(&self) -> FramerateLimit {
+            FramerateLimit::Limited(30)
+        }
+
+        fn background_framerate(&self) -> FramerateLimit {
+            FramerateLimit::Limited(5)
+        }
+    }
+
+    fn runner(app: App) -> AppRunner {
+        let (_, mut runner) = app.into_runner().unwrap();
+        // startup would have inserted it
+        runner.resources.insert(Stubborn);
+        runner
+    }
+
+    #[test]
+    fn forwards_framework_hooks() {
+        let mut app = App::new(EngineConfig::default());
+        app.add_plugin(FrameworkPlugin::<Stubborn>::default());
+        let mut runner = runner(app);
+        let mut ctx = Context::new(Time::new());
+
+        assert!(!runner.close_requested(&mut ctx));
+        assert_eq!(
+            runner.window_title(&FrameStats::default()).as_deref(),
+            Some("stubborn")
+        );
+        assert!(matches!(
+            runner.maximum_framerate(),
+            FramerateLimit::Limited(30)
+        ));
+        assert!(matches!(
+            runner.background_framerate(),
+            FramerateLimit::Limited(5)
+        ));
+    }
+
+    #[test]
+    fn app_limits_replace_the_framework_ones() {
+        let mut app = App::new(EngineConfig::default());
+        app.add_plugin(FrameworkPlugin::<Stubborn>::default())
+            .set_maximum_framerate(FramerateLimit::Limited(144));
+        let runner = runner(app);
+
+        assert!(matches!(
+            runner.maximum_framerate(),
+            FramerateLimit::Limited(144)
+        ));
+        assert!(matches!(
+            runner.background_framerate(),
+            FramerateLimit::Limited(144)
+        ));
+    }
+}
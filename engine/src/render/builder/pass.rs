@@ -1,15 +1,23 @@
 use typed_builder::TypedBuilder;
 use wgpu::{Color, RenderPassColorAttachment, TextureView};
 
+use crate::render::profiler::GpuProfiler;
+
 #[derive(TypedBuilder)]
 pub struct RenderPassBuilder<'a> {
     #[builder(default, setter(strip_option))]
     label: Option<&'a str>,
     color_attachments: &'a [Option<RenderPassColorAttachment<'a>>],
-    #[builder(default, setter(strip_option))]
+    #[builder(default, setter(into))]
     depth_stencil_attachment: Option<wgpu::RenderPassDepthStencilAttachment<'a>>,
 }
 
+#[derive(TypedBuilder)]
+pub struct ComputePassBuilder<'a> {
+    #[builder(default, setter(strip_option))]
+    label: Option<&'a str>,
+}
+
 #[derive(TypedBuilder)]
 pub struct RenderPassColorAttachmentBuilder<'a> {
     #[builder(default, setter(strip_option))]
@@ -18,13 +26,43 @@ pub struct RenderPassColorAttachmentBuilder<'a> {
 }
 
 impl<'a> RenderPassBuilder<'a> {
-    pub fn begin(self, encoder: &'a mut wgpu::CommandEncoder) -> wgpu::RenderPass {
+    pub fn begin(self, encoder: &'a mut wgpu::CommandEncoder) -> wgpu::RenderPass<'a> {
         encoder.begin_render_pass(&wgpu::RenderPassDescriptor::<'a, 'a> {
             label: self.label,
             color_attachments: self.color_attachments,
             depth_stencil_attachment: self.depth_stencil_attachment.clone(),
         })
     }
+
+    /// Begins the pass and measures it as a `name` scope of `profiler`, see
+    /// [`GpuProfiler::begin_pass_scope`] for when the scope ends.
+    pub fn begin_profiled(
+        self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        profiler: &mut GpuProfiler,
+        name: impl Into<String>,
+    ) -> wgpu::RenderPass<'a> {
+        profiler.begin_pass_scope(name, encoder);
+        self.begin(encoder)
+    }
+}
+
+impl<'a> ComputePassBuilder<'a> {
+    pub fn begin(self, encoder: &'a mut wgpu::CommandEncoder) -> wgpu::ComputePass<'a> {
+        encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: self.label })
+    }
+
+    /// Begins the pass and measures it as a `name` scope of `profiler`, see
+    /// [`GpuProfiler::begin_pass_scope`] for when the scope ends.
+    pub fn begin_profiled(
+        self,
+        encoder: &'a mut wgpu::CommandEncoder,
+        profiler: &mut GpuProfiler,
+        name: impl Into<String>,
+    ) -> wgpu::ComputePass<'a> {
+        profiler.begin_pass_scope(name, encoder);
+        self.begin(encoder)
+    }
 }
 
 impl<'a> RenderPassColorAttachmentBuilder<'a> {
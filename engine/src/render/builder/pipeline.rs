@@ -12,6 +12,8 @@ pub struct PipelineBuilder<'a> {
     label: Option<&'a str>,
     #[builder(default)]
     fragment: bool,
+    #[builder(default = 1)]
+    sample_count: u32,
 }
 
 #[derive(TypedBuilder)]
@@ -56,7 +58,9 @@ impl<'a> PipelineBuilder<'a> {
                 cull_mode: Some(wgpu::Face::Back),
                 polygon_mode: wgpu::PolygonMode::Fill,
                 conservative: false,
-                unclipped_depth: true,
+                unclipped_depth: device
+                    .features()
+                    .contains(wgpu::Features::DEPTH_CLIP_CONTROL),
             },
             depth_stencil: self.depth_format.map(|format| wgpu::DepthStencilState {
                 format,
@@ -66,7 +70,7 @@ impl<'a> PipelineBuilder<'a> {
                 bias: wgpu::DepthBiasState::default(),
             }),
             multisample: wgpu::MultisampleState {
-                count: 1,
+                count: self.sample_count,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
@@ -1,10 +1,8 @@
 use std::collections::{hash_map::Iter, HashMap};
 
+use glam::Mat4;
 use typed_builder::TypedBuilder;
-use wgpu::{
-    util::{BufferInitDescriptor, DeviceExt},
-    BufferUsages,
-};
+use wgpu::{util::DeviceExt, BufferUsages};
 
 use crate::render::{
     handle::HandleId,
@@ -16,8 +14,9 @@ use crate::render::{
 #[derive(Debug)]
 pub struct Bundles<T: IntoRawBinder> {
     pub(crate) queued_bundles: HashMap<HandleId, MeshBundle<T>>,
-    pub(crate) queued_instances: HashMap<HandleId, Transform>,
+    pub(crate) queued_instances: HashMap<HandleId, Vec<Transform>>,
     pub(crate) bundles: HashMap<HandleId, RawMeshBundle<T::RawBinder>>,
+    pub(crate) keep_meshes: bool,
 }
 
 impl<T: IntoRawBinder> Default for Bundles<T> {
@@ -26,37 +25,47 @@ impl<T: IntoRawBinder> Default for Bundles<T> {
             queued_bundles: HashMap::default(),
             queued_instances: HashMap::default(),
             bundles: HashMap::default(),
+            keep_meshes: false,
         }
     }
 }
 
 impl<T: IntoRawBinder> Bundles<T> {
     pub fn add(&mut self, bundle: MeshBundle<T>) -> HandleId {
-        let id: HandleId = self.queued_bundles.len().into();
+        let id: HandleId = self.queued_bundles.len() + self.bundles.len();
         self.queued_bundles.insert(id, bundle);
         id
     }
 
+    /// Keeps a CPU copy of the meshes processed from now on, for [`Bundles::baked_mesh`].
+    pub fn set_keep_meshes(&mut self, keep: bool) -> &mut Self {
+        self.keep_meshes = keep;
+        self
+    }
+
     pub fn instance(&mut self, id: HandleId, transform: Transform) {
-        self.queued_instances.insert(id, transform);
+        self.queued_instances.entry(id).or_default().push(transform);
     }
 
     pub fn process_queue(&mut self, params: &RawParams) {
         for (id, bundle) in self.queued_bundles.drain() {
-            let raw = bundle.into_raw(params);
-            let id = id;
+            let mut raw = bundle.into_raw(params);
+
+            if self.keep_meshes {
+                raw.source = Some(bundle.mesh);
+            }
 
             self.bundles.insert(id, raw);
         }
 
-        for (id, transform) in self.queued_instances.drain() {
-            let raw = transform.to_raw(params);
-            let id = id;
-
+        for (id, transforms) in self.queued_instances.drain() {
             let raw_bundle = self.bundles.get_mut(&id);
 
             if let Some(raw_bundle) = raw_bundle {
-                raw_bundle.instance(&params, raw);
+                raw_bundle.instance_all(
+                    params,
+                    transforms.iter().map(|transform| transform.to_raw(params)),
+                );
             }
         }
     }
@@ -64,6 +73,35 @@ impl<T: IntoRawBinder> Bundles<T> {
     pub fn iter(&self) -> Iter<'_, usize, RawMeshBundle<<T as IntoRawBinder>::RawBinder>> {
         self.bundles.iter()
     }
+
+    /// Every processed bundle merged into a single mesh, with each of its instances baked in.
+    /// Bundles without instances are drawn once as they are, so they're merged as they are too.
+    ///
+    /// Only bundles processed while [`Bundles::set_keep_meshes`] was on are included.
+    pub fn baked_mesh(&self) -> Mesh {
+        let mut ids = self.bundles.keys().copied().collect::<Vec<_>>();
+        ids.sort_unstable();
+
+        let meshes = ids.into_iter().flat_map(|id| {
+            let bundle = &self.bundles[&id];
+            let source = bundle.source.as_ref();
+
+            let instances = match bundle.instances.is_empty() {
+                true => vec![Mat4::IDENTITY],
+                false => bundle
+                    .instances
+                    .iter()
+                    .map(|instance| Mat4::from_cols_array_2d(&instance.model))
+                    .collect(),
+            };
+
+            instances
+                .into_iter()
+                .filter_map(move |model| Some(source?.transformed_by(model)))
+        });
+
+        Mesh::merge(meshes)
+    }
 }
 
 #[derive(TypedBuilder, Debug)]
@@ -77,6 +115,8 @@ pub struct MeshBundle<T: IntoRawBinder> {
 #[derive(Debug)]
 pub struct RawMeshBundle<T: RawBinder> {
     pub(crate) mesh: RawMesh,
+    /// The mesh as it was uploaded, if [`Bundles::set_keep_meshes`] is on.
+    pub(crate) source: Option<Mesh>,
     pub(crate) material: T,
     pub(crate) instances: Vec<TransformRaw>,
     pub(crate) instance_buffer: Option<wgpu::Buffer>,
@@ -84,7 +124,16 @@ pub struct RawMeshBundle<T: RawBinder> {
 
 impl<T: RawBinder> RawMeshBundle<T> {
     pub fn instance(&mut self, params: &RawParams, instance: TransformRaw) {
-        self.instances.push(instance);
+        self.instance_all(params, [instance]);
+    }
+
+    /// Adds every instance, and recreates the instance buffer only once.
+    pub fn instance_all(
+        &mut self,
+        params: &RawParams,
+        instances: impl IntoIterator<Item = TransformRaw>,
+    ) {
+        self.instances.extend(instances);
         self.update_buffer(params);
     }
 
@@ -123,6 +172,7 @@ impl<T: IntoRawBinder> IntoRawBinder for MeshBundle<T> {
 
         RawMeshBundle {
             mesh: raw_mesh,
+            source: None,
             material: raw_mat,
             instances: Vec::new(),
             instance_buffer: None,
@@ -38,7 +38,17 @@ impl Camera {
         let view = Mat4::look_at_rh(self.eye, self.target, self.up);
         let proj = Mat4::perspective_rh(self.fovy.to_radians(), self.aspect, self.znear, self.zfar);
 
-        return TRANSLATION_MATRIX * proj * view;
+        TRANSLATION_MATRIX * proj * view
+    }
+
+    pub fn set_aspect(&mut self, aspect: f32) {
+        self.aspect = aspect;
+    }
+}
+
+impl Default for CameraPerspective {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -53,13 +63,13 @@ impl CameraPerspective {
         self.view_projection = camera.build_view_matrix().to_cols_array_2d();
     }
 
-    pub fn create_raw_bind<'a>(&self, device: &wgpu::Device, contents: &'a [u8]) -> CameraBind {
+    pub fn create_raw_bind(&self, device: &wgpu::Device, contents: &[u8]) -> CameraBind {
         CameraBind::new(device, contents)
     }
 }
 
 impl CameraBind {
-    pub fn new<'a>(device: &wgpu::Device, contents: &'a [u8]) -> Self {
+    pub fn new(device: &wgpu::Device, contents: &[u8]) -> Self {
         let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("camera_bind_group_layout"),
             entries: &[wgpu::BindGroupLayoutEntry {
@@ -98,7 +108,7 @@ impl CameraBind {
         render_pass.set_bind_group(idx, &self.bind_group, &[]);
     }
 
-    pub fn update_buffer<'a>(&mut self, queue: &wgpu::Queue, slice: &'a [u8]) {
+    pub fn update_buffer(&mut self, queue: &wgpu::Queue, slice: &[u8]) {
         queue.write_buffer(&self.buffer, 0, slice);
     }
 }
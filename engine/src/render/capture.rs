@@ -0,0 +1,286 @@
+use std::{fs::File, io::BufWriter, num::NonZeroU32, path::Path};
+
+use anyhow::{anyhow, bail, Context};
+
+use super::color::linear_to_srgb;
+
+/// A tightly packed, 8-bit RGBA image read back from the GPU.
+///
+/// Pixels are stored the way they would be displayed, so a frame rendered into an sRGB target
+/// is sRGB encoded here as well.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl RgbaImage {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; (width * height * 4) as usize],
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let idx = ((y * self.width + x) * 4) as usize;
+        let mut pixel = [0; 4];
+
+        pixel.copy_from_slice(&self.pixels[idx..idx + 4]);
+        pixel
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, pixel: [u8; 4]) {
+        let idx = ((y * self.width + x) * 4) as usize;
+        self.pixels[idx..idx + 4].copy_from_slice(&pixel);
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path).with_context(|| format!("unable to create {path:?}"))?;
+
+        let mut encoder = png::Encoder::new(BufWriter::new(file), self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+
+        Ok(())
+    }
+
+    pub fn load_png(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("unable to open {path:?}"))?;
+
+        let mut decoder = png::Decoder::new(file);
+        // expand palettes and low bit depths, and strip 16-bit channels down to 8 bits.
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        let data = &buffer[..info.buffer_size()];
+
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => data.to_vec(),
+            png::ColorType::Rgb => data
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => data
+                .chunks_exact(2)
+                .flat_map(|p| [p[0], p[0], p[0], p[1]])
+                .collect(),
+            png::ColorType::Grayscale => data.iter().flat_map(|&p| [p, p, p, 255]).collect(),
+            png::ColorType::Indexed => bail!("{path:?} was not expanded from an indexed image"),
+        };
+
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+}
+
+/// A texture copy that has been recorded into a command encoder, but not read back yet.
+///
+/// Call [`PendingCapture::read`] once the encoder has been submitted.
+pub struct PendingCapture {
+    buffer: wgpu::Buffer,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+}
+
+/// Creates a texture matching `config` that frames can be rendered into and copied out of.
+pub fn create_capture_texture(
+    device: &wgpu::Device,
+    config: &wgpu::SurfaceConfiguration,
+) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("capture target"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: config.format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+/// Records a copy of `texture` into a mappable buffer.
+///
+/// The texture must have been created with [`wgpu::TextureUsages::COPY_SRC`].
+pub fn copy_texture(
+    device: &wgpu::Device,
+    encoder: &mut wgpu::CommandEncoder,
+    texture: &wgpu::Texture,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+) -> anyhow::Result<PendingCapture> {
+    if !is_capturable(format) {
+        bail!("capturing {format:?} textures is not supported");
+    }
+
+    let bytes_per_pixel = format.describe().block_size as u32;
+    let unpadded_bytes_per_row = width * bytes_per_pixel;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("frame capture buffer"),
+        size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(padded_bytes_per_row),
+                rows_per_image: NonZeroU32::new(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+
+    Ok(PendingCapture {
+        buffer,
+        format,
+        width,
+        height,
+        padded_bytes_per_row,
+    })
+}
+
+/// Copies `texture` to the CPU in its own submission, blocking until it is available.
+pub fn capture_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+) -> anyhow::Result<RgbaImage> {
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Capture Encoder"),
+    });
+
+    let pending = copy_texture(device, &mut encoder, texture, format, width, height)?;
+    queue.submit(std::iter::once(encoder.finish()));
+
+    pending.read(device)
+}
+
+pub fn is_capturable(format: wgpu::TextureFormat) -> bool {
+    use wgpu::TextureFormat::*;
+
+    matches!(
+        format,
+        Rgba8Unorm | Rgba8UnormSrgb | Bgra8Unorm | Bgra8UnormSrgb | Rgb10a2Unorm | Rgba16Float
+    )
+}
+
+impl PendingCapture {
+    /// Maps the buffer, blocking until the GPU has finished the copy, and converts it to RGBA.
+    pub fn read(self, device: &wgpu::Device) -> anyhow::Result<RgbaImage> {
+        let slice = self.buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+
+        receiver
+            .recv()
+            .map_err(|_| anyhow!("capture buffer was dropped before being mapped"))??;
+
+        let bytes_per_pixel = self.format.describe().block_size as usize;
+        let mut image = RgbaImage::new(self.width, self.height);
+
+        {
+            let data = slice.get_mapped_range();
+
+            for (y, row) in data
+                .chunks_exact(self.padded_bytes_per_row as usize)
+                .enumerate()
+            {
+                let row = &row[..self.width as usize * bytes_per_pixel];
+
+                for (x, texel) in row.chunks_exact(bytes_per_pixel).enumerate() {
+                    image.set_pixel(x as u32, y as u32, convert_texel(self.format, texel));
+                }
+            }
+        }
+
+        self.buffer.unmap();
+        Ok(image)
+    }
+}
+
+fn convert_texel(format: wgpu::TextureFormat, texel: &[u8]) -> [u8; 4] {
+    use wgpu::TextureFormat::*;
+
+    match format {
+        Rgba8Unorm | Rgba8UnormSrgb => [texel[0], texel[1], texel[2], texel[3]],
+        Bgra8Unorm | Bgra8UnormSrgb => [texel[2], texel[1], texel[0], texel[3]],
+        Rgb10a2Unorm => {
+            let packed = u32::from_le_bytes([texel[0], texel[1], texel[2], texel[3]]);
+            let channel = |shift: u32| (((packed >> shift) & 0x3ff) * 255 / 0x3ff) as u8;
+
+            [
+                channel(0),
+                channel(10),
+                channel(20),
+                ((packed >> 30) * 255 / 3) as u8,
+            ]
+        }
+        Rgba16Float => {
+            let channel =
+                |i: usize| f16_to_f32(u16::from_le_bytes([texel[i * 2], texel[i * 2 + 1]]));
+            // float targets hold linear values, while the image is sRGB encoded.
+            let encode = |v: f32| (linear_to_srgb(v.clamp(0.0, 1.0) as f64) * 255.0).round() as u8;
+
+            [
+                encode(channel(0)),
+                encode(channel(1)),
+                encode(channel(2)),
+                (channel(3).clamp(0.0, 1.0) * 255.0).round() as u8,
+            ]
+        }
+        _ => unreachable!("{format:?} is not capturable"),
+    }
+}
+
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32;
+
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => sign * f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
@@ -1,7 +1,13 @@
+use std::{fmt, ops, str::FromStr};
+
 use glam::Vec4;
 
+/// An RGBA color, stored as linear values so lighting and blending math is correct.
+///
+/// Colors picked in a paint program or a color picker are sRGB encoded, and should be made with
+/// [`Color::srgb`]. Conversions from arrays take linear values, like [`Color::linear`].
 #[repr(C)]
-#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Debug, Copy, Clone, PartialEq, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct Color {
     pub(crate) r: f64,
     pub(crate) g: f64,
@@ -10,6 +16,34 @@ pub struct Color {
 }
 
 impl Color {
+    pub fn linear(r: f64, g: f64, b: f64, a: f64) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// A color from sRGB encoded values, alpha is never encoded.
+    pub fn srgb(r: f64, g: f64, b: f64, a: f64) -> Self {
+        Self {
+            r: srgb_to_linear(r),
+            g: srgb_to_linear(g),
+            b: srgb_to_linear(b),
+            a,
+        }
+    }
+
+    pub fn to_linear(self) -> [f64; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    pub fn to_srgb(self) -> [f64; 4] {
+        [
+            linear_to_srgb(self.r),
+            linear_to_srgb(self.g),
+            linear_to_srgb(self.b),
+            self.a,
+        ]
+    }
+
+    /// The linear values, which is what sRGB and float targets expect. See [`Color::wgpu_for`].
     pub fn wgpu(&self) -> wgpu::Color {
         wgpu::Color {
             r: self.r,
@@ -18,23 +52,507 @@ impl Color {
             a: self.a,
         }
     }
+
+    /// The values to clear a target of `format` with, so the color shows up as intended.
+    pub fn wgpu_for(&self, format: wgpu::TextureFormat) -> wgpu::Color {
+        let [r, g, b, a] = self.to_format(format);
+
+        wgpu::Color { r, g, b, a }
+    }
+
+    /// The values a shader should output into a target of `format`, e.g. for uniforms.
+    pub fn to_format(self, format: wgpu::TextureFormat) -> [f64; 4] {
+        match expects_srgb_values(format) {
+            true => self.to_srgb(),
+            false => self.to_linear(),
+        }
+    }
+
+    /// Interpolates the linear values, which is physically correct for mixing light.
+    pub fn lerp(self, other: Color, t: f64) -> Self {
+        self + (other - self) * t
+    }
+
+    /// Interpolates the sRGB encoded values, like most image editors and CSS do.
+    pub fn lerp_srgb(self, other: Color, t: f64) -> Self {
+        let [r, g, b, a] = lerp_array(self.to_srgb(), other.to_srgb(), t);
+        Self::srgb(r, g, b, a)
+    }
+
+    /// Interpolates in OKLab, which keeps the perceived lightness and hue changing evenly.
+    pub fn lerp_oklab(self, other: Color, t: f64) -> Self {
+        let [l, a, b, alpha] = lerp_array(self.to_oklab(), other.to_oklab(), t);
+        Self::oklab(l, a, b, alpha)
+    }
+
+    /// Multiplies the color by its alpha.
+    pub fn premultiplied(self) -> Self {
+        Self::linear(self.r * self.a, self.g * self.a, self.b * self.a, self.a)
+    }
+
+    /// Undoes [`Color::premultiplied`], a fully transparent color stays black.
+    pub fn unpremultiplied(self) -> Self {
+        match self.a {
+            a if a > 0.0 => Self::linear(self.r / a, self.g / a, self.b / a, a),
+            _ => Self::linear(0.0, 0.0, 0.0, 0.0),
+        }
+    }
+
+    /// Blends this color on top of `dst` with `mode`. Both are straight alpha, as is the result.
+    pub fn blend(self, dst: Color, mode: BlendMode) -> Self {
+        let (src_a, dst_a) = (self.a, dst.a);
+        let alpha = src_a + dst_a * (1.0 - src_a);
+
+        if alpha <= 0.0 {
+            return Self::linear(0.0, 0.0, 0.0, 0.0);
+        }
+
+        let mix = |src: f64, dst: f64| {
+            let blended = match mode {
+                BlendMode::Over => src,
+                BlendMode::Add => src + dst,
+                BlendMode::Multiply => src * dst,
+                BlendMode::Screen => src + dst - src * dst,
+            };
+
+            // the blended value only shows where both colors cover the pixel.
+            let premultiplied =
+                src_a * dst_a * blended + src_a * (1.0 - dst_a) * src + (1.0 - src_a) * dst_a * dst;
+            premultiplied / alpha
+        };
+
+        Self::linear(
+            mix(self.r, dst.r),
+            mix(self.g, dst.g),
+            mix(self.b, dst.b),
+            alpha,
+        )
+    }
+
+    /// The relative luminance, as defined by WCAG.
+    pub fn luminance(self) -> f64 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    /// The WCAG contrast ratio between two colors, from 1 to 21. Alpha is ignored.
+    pub fn contrast(self, other: Color) -> f64 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    /// Whichever of black or white text reads better on this color.
+    pub fn contrasting(self) -> Self {
+        let black = Self::linear(0.0, 0.0, 0.0, 1.0);
+        let white = Self::linear(1.0, 1.0, 1.0, 1.0);
+
+        match self.contrast(black) >= self.contrast(white) {
+            true => black,
+            false => white,
+        }
+    }
+
+    /// Clamps every component to `0.0..=1.0`.
+    pub fn clamped(self) -> Self {
+        Self::linear(
+            self.r.clamp(0.0, 1.0),
+            self.g.clamp(0.0, 1.0),
+            self.b.clamp(0.0, 1.0),
+            self.a.clamp(0.0, 1.0),
+        )
+    }
+
+    /// The same color with another alpha.
+    pub fn with_alpha(self, a: f64) -> Self {
+        Self { a, ..self }
+    }
+
+    /// A color from sRGB encoded bytes, like `[255, 128, 0, 255]`.
+    pub fn from_rgba8([r, g, b, a]: [u8; 4]) -> Self {
+        let unit = |value: u8| value as f64 / 255.0;
+        Self::srgb(unit(r), unit(g), unit(b), unit(a))
+    }
+
+    /// The sRGB encoded bytes of the color, components outside `0.0..=1.0` are clamped.
+    pub fn to_rgba8(self) -> [u8; 4] {
+        self.to_srgb()
+            .map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8)
+    }
+
+    /// Parses `#RGB`, `#RGBA`, `#RRGGBB` or `#RRGGBBAA`, the `#` is optional.
+    pub fn hex(hex: &str) -> Result<Self, ParseColorError> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ParseColorError::InvalidHex(hex.to_string()));
+        }
+
+        let digit = |idx: usize| u8::from_str_radix(&digits[idx..idx + 1], 16).unwrap_or(0);
+        let byte = |idx: usize| u8::from_str_radix(&digits[idx..idx + 2], 16).unwrap_or(0);
+
+        let rgba = match digits.len() {
+            3 | 4 => {
+                let short = |idx: usize| match idx < digits.len() {
+                    true => digit(idx) * 17,
+                    false => 255,
+                };
+                [short(0), short(1), short(2), short(3)]
+            }
+            6 | 8 => {
+                let alpha = match digits.len() {
+                    8 => byte(6),
+                    _ => 255,
+                };
+                [byte(0), byte(2), byte(4), alpha]
+            }
+            _ => return Err(ParseColorError::InvalidHex(hex.to_string())),
+        };
+
+        Ok(Self::from_rgba8(rgba))
+    }
+
+    /// Formats the color as `#rrggbb`, or `#rrggbbaa` if it isn't opaque.
+    pub fn to_hex(self) -> String {
+        let [r, g, b, a] = self.to_rgba8();
+
+        match a {
+            255 => format!("#{r:02x}{g:02x}{b:02x}"),
+            _ => format!("#{r:02x}{g:02x}{b:02x}{a:02x}"),
+        }
+    }
+
+    /// One of the CSS named colors, e.g. `"rebeccapurple"`, ignoring case.
+    pub fn named(name: &str) -> Option<Self> {
+        NAMED_COLORS
+            .iter()
+            .find(|(named, _)| named.eq_ignore_ascii_case(name))
+            .map(|(_, rgba)| Self::from_rgba8(rgba.to_be_bytes()))
+    }
+
+    /// The CSS name of the color, if it's exactly one of them.
+    pub fn name(&self) -> Option<&'static str> {
+        let rgba = u32::from_be_bytes(self.to_rgba8());
+
+        NAMED_COLORS
+            .iter()
+            .find(|(_, named)| *named == rgba)
+            .map(|(name, _)| *name)
+    }
+
+    /// A color from hue in degrees, and saturation and value in `0.0..=1.0`, on sRGB encoded values.
+    pub fn hsv(hue: f64, saturation: f64, value: f64, alpha: f64) -> Self {
+        let chroma = value * saturation;
+        let [r, g, b] = hue_to_rgb(hue, chroma);
+        let min = value - chroma;
+
+        Self::srgb(r + min, g + min, b + min, alpha)
+    }
+
+    /// Hue in degrees, saturation, value and alpha.
+    pub fn to_hsv(self) -> [f64; 4] {
+        let [r, g, b, a] = self.to_srgb();
+        let max = r.max(g).max(b);
+        let chroma = max - r.min(g).min(b);
+
+        let saturation = match max {
+            max if max > 0.0 => chroma / max,
+            _ => 0.0,
+        };
+
+        [rgb_to_hue(r, g, b), saturation, max, a]
+    }
+
+    /// A color from hue in degrees, and saturation and lightness in `0.0..=1.0`, on sRGB encoded values.
+    pub fn hsl(hue: f64, saturation: f64, lightness: f64, alpha: f64) -> Self {
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let [r, g, b] = hue_to_rgb(hue, chroma);
+        let min = lightness - chroma / 2.0;
+
+        Self::srgb(r + min, g + min, b + min, alpha)
+    }
+
+    /// Hue in degrees, saturation, lightness and alpha.
+    pub fn to_hsl(self) -> [f64; 4] {
+        let [r, g, b, a] = self.to_srgb();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let lightness = (max + min) / 2.0;
+
+        let saturation = match lightness {
+            l if l > 0.0 && l < 1.0 => (max - min) / (1.0 - (2.0 * l - 1.0).abs()),
+            _ => 0.0,
+        };
+
+        [rgb_to_hue(r, g, b), saturation, lightness, a]
+    }
+
+    /// A color from the perceptual OKLab space, with lightness in `0.0..=1.0` and `a` and `b`
+    /// roughly in `-0.4..=0.4`.
+    pub fn oklab(lightness: f64, a: f64, b: f64, alpha: f64) -> Self {
+        let l = (lightness + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+        let m = (lightness - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+        let s = (lightness - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+
+        Self::linear(
+            4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+            -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+            -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+            alpha,
+        )
+    }
+
+    /// Lightness, `a`, `b` and alpha.
+    pub fn to_oklab(self) -> [f64; 4] {
+        let l = (0.4122214708 * self.r + 0.5363325363 * self.g + 0.0514459929 * self.b).cbrt();
+        let m = (0.2119034982 * self.r + 0.6806995451 * self.g + 0.1073969566 * self.b).cbrt();
+        let s = (0.0883024619 * self.r + 0.2817188376 * self.g + 0.6299787005 * self.b).cbrt();
+
+        [
+            0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+            1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+            0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+            self.a,
+        ]
+    }
+
+    /// OKLab in polar form, with chroma roughly in `0.0..=0.4` and hue in degrees.
+    pub fn oklch(lightness: f64, chroma: f64, hue: f64, alpha: f64) -> Self {
+        let (sin, cos) = hue.to_radians().sin_cos();
+        Self::oklab(lightness, chroma * cos, chroma * sin, alpha)
+    }
+
+    /// Lightness, chroma, hue in degrees and alpha.
+    pub fn to_oklch(self) -> [f64; 4] {
+        let [lightness, a, b, alpha] = self.to_oklab();
+        let hue = b.atan2(a).to_degrees().rem_euclid(360.0);
+
+        [lightness, a.hypot(b), hue, alpha]
+    }
+}
+
+/// How [`Color::blend`] combines a color with the one beneath it, where both are opaque.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BlendMode {
+    /// The top color replaces the one beneath it.
+    #[default]
+    Over,
+    Add,
+    Multiply,
+    Screen,
+}
+
+macro_rules! impl_component_op {
+    ($trait:ident, $fn:ident, $assign_trait:ident, $assign_fn:ident, $op:tt) => {
+        impl ops::$trait for Color {
+            type Output = Color;
+
+            fn $fn(self, rhs: Color) -> Color {
+                Color::linear(self.r $op rhs.r, self.g $op rhs.g, self.b $op rhs.b, self.a $op rhs.a)
+            }
+        }
+
+        impl ops::$trait<f64> for Color {
+            type Output = Color;
+
+            fn $fn(self, rhs: f64) -> Color {
+                Color::linear(self.r $op rhs, self.g $op rhs, self.b $op rhs, self.a $op rhs)
+            }
+        }
+
+        impl ops::$assign_trait for Color {
+            fn $assign_fn(&mut self, rhs: Color) {
+                *self = *self $op rhs;
+            }
+        }
+
+        impl ops::$assign_trait<f64> for Color {
+            fn $assign_fn(&mut self, rhs: f64) {
+                *self = *self $op rhs;
+            }
+        }
+    };
+}
+
+// component-wise on the linear values, alpha included.
+impl_component_op!(Add, add, AddAssign, add_assign, +);
+impl_component_op!(Sub, sub, SubAssign, sub_assign, -);
+impl_component_op!(Mul, mul, MulAssign, mul_assign, *);
+impl_component_op!(Div, div, DivAssign, div_assign, /);
+
+fn lerp_array(from: [f64; 4], to: [f64; 4], t: f64) -> [f64; 4] {
+    [0, 1, 2, 3].map(|idx| from[idx] + (to[idx] - from[idx]) * t)
+}
+
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    /// Parses a hex color like `#ff8800`, or a CSS color name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        match s.starts_with('#') {
+            true => Self::hex(s),
+            false => Self::named(s)
+                .or_else(|| Self::hex(s).ok())
+                .ok_or_else(|| ParseColorError::UnknownName(s.to_string())),
+        }
+    }
 }
 
-impl Into<Vec4> for Color {
-    fn into(self) -> Vec4 {
-        Vec4::new(self.r as f32, self.g as f32, self.b as f32, self.a as f32)
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
     }
 }
 
-impl Into<[f32; 4]> for Color {
-    fn into(self) -> [f32; 4] {
-        [self.r as f32, self.g as f32, self.b as f32, self.a as f32]
+impl From<[u8; 4]> for Color {
+    fn from(rgba: [u8; 4]) -> Self {
+        Self::from_rgba8(rgba)
     }
 }
 
-impl Into<[f64; 4]> for Color {
-    fn into(self) -> [f64; 4] {
-        [self.r as f64, self.g as f64, self.b as f64, self.a as f64]
+impl From<Color> for [u8; 4] {
+    fn from(color: Color) -> Self {
+        color.to_rgba8()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseColorError {
+    InvalidHex(String),
+    UnknownName(String),
+}
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseColorError::InvalidHex(hex) => write!(f, "{hex:?} isn't a hex color"),
+            ParseColorError::UnknownName(name) => write!(f, "{name:?} isn't a known color"),
+        }
+    }
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// Colors are written as arrays of linear values so they read back exactly, and read from those,
+/// RGB arrays, hex strings or CSS names.
+#[cfg(feature = "color-serde")]
+mod serde_impl {
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::Color;
+
+    impl Serialize for Color {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.to_linear().serialize(serializer)
+        }
+    }
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        Text(String),
+        Rgb([f64; 3]),
+        Rgba([f64; 4]),
+    }
+
+    impl<'de> Deserialize<'de> for Color {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            match Repr::deserialize(deserializer)? {
+                Repr::Text(text) => text.parse().map_err(de::Error::custom),
+                Repr::Rgb(rgb) => Ok(rgb.into()),
+                Repr::Rgba(rgba) => Ok(rgba.into()),
+            }
+        }
+    }
+}
+
+/// Splits a hue in degrees and a chroma into RGB, without the lightness offset.
+fn hue_to_rgb(hue: f64, chroma: f64) -> [f64; 3] {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+    match sector as u32 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    }
+}
+
+fn rgb_to_hue(r: f64, g: f64, b: f64) -> f64 {
+    let max = r.max(g).max(b);
+    let chroma = max - r.min(g).min(b);
+
+    if chroma == 0.0 {
+        return 0.0;
+    }
+
+    let hue = if max == r {
+        ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+
+    hue * 60.0
+}
+
+/// Whether a target of `format` stores exactly what's written to it, so values have to be sRGB
+/// encoded already to display right. sRGB formats encode on write, and float formats hold
+/// linear values.
+pub fn expects_srgb_values(format: wgpu::TextureFormat) -> bool {
+    use wgpu::TextureFormat::*;
+
+    matches!(
+        format,
+        R8Unorm | Rg8Unorm | Rgba8Unorm | Bgra8Unorm | Rgb10a2Unorm | Rgba16Unorm
+    )
+}
+
+pub fn srgb_to_linear(value: f64) -> f64 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(value: f64) -> f64 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+impl From<Color> for Vec4 {
+    fn from(color: Color) -> Self {
+        Vec4::new(
+            color.r as f32,
+            color.g as f32,
+            color.b as f32,
+            color.a as f32,
+        )
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(color: Color) -> Self {
+        [
+            color.r as f32,
+            color.g as f32,
+            color.b as f32,
+            color.a as f32,
+        ]
+    }
+}
+
+impl From<Color> for [f64; 4] {
+    fn from(color: Color) -> Self {
+        [color.r, color.g, color.b, color.a]
     }
 }
 
@@ -71,3 +589,420 @@ impl From<[f32; 3]> for Color {
         }
     }
 }
+
+/// The CSS named colors, as sRGB encoded `0xRRGGBBAA`.
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ffff),
+    ("antiquewhite", 0xfaebd7ff),
+    ("aqua", 0x00ffffff),
+    ("aquamarine", 0x7fffd4ff),
+    ("azure", 0xf0ffffff),
+    ("beige", 0xf5f5dcff),
+    ("bisque", 0xffe4c4ff),
+    ("black", 0x000000ff),
+    ("blanchedalmond", 0xffebcdff),
+    ("blue", 0x0000ffff),
+    ("blueviolet", 0x8a2be2ff),
+    ("brown", 0xa52a2aff),
+    ("burlywood", 0xdeb887ff),
+    ("cadetblue", 0x5f9ea0ff),
+    ("chartreuse", 0x7fff00ff),
+    ("chocolate", 0xd2691eff),
+    ("coral", 0xff7f50ff),
+    ("cornflowerblue", 0x6495edff),
+    ("cornsilk", 0xfff8dcff),
+    ("crimson", 0xdc143cff),
+    ("cyan", 0x00ffffff),
+    ("darkblue", 0x00008bff),
+    ("darkcyan", 0x008b8bff),
+    ("darkgoldenrod", 0xb8860bff),
+    ("darkgray", 0xa9a9a9ff),
+    ("darkgreen", 0x006400ff),
+    ("darkgrey", 0xa9a9a9ff),
+    ("darkkhaki", 0xbdb76bff),
+    ("darkmagenta", 0x8b008bff),
+    ("darkolivegreen", 0x556b2fff),
+    ("darkorange", 0xff8c00ff),
+    ("darkorchid", 0x9932ccff),
+    ("darkred", 0x8b0000ff),
+    ("darksalmon", 0xe9967aff),
+    ("darkseagreen", 0x8fbc8fff),
+    ("darkslateblue", 0x483d8bff),
+    ("darkslategray", 0x2f4f4fff),
+    ("darkslategrey", 0x2f4f4fff),
+    ("darkturquoise", 0x00ced1ff),
+    ("darkviolet", 0x9400d3ff),
+    ("deeppink", 0xff1493ff),
+    ("deepskyblue", 0x00bfffff),
+    ("dimgray", 0x696969ff),
+    ("dimgrey", 0x696969ff),
+    ("dodgerblue", 0x1e90ffff),
+    ("firebrick", 0xb22222ff),
+    ("floralwhite", 0xfffaf0ff),
+    ("forestgreen", 0x228b22ff),
+    ("fuchsia", 0xff00ffff),
+    ("gainsboro", 0xdcdcdcff),
+    ("ghostwhite", 0xf8f8ffff),
+    ("gold", 0xffd700ff),
+    ("goldenrod", 0xdaa520ff),
+    ("gray", 0x808080ff),
+    ("green", 0x008000ff),
+    ("greenyellow", 0xadff2fff),
+    ("grey", 0x808080ff),
+    ("honeydew", 0xf0fff0ff),
+    ("hotpink", 0xff69b4ff),
+    ("indianred", 0xcd5c5cff),
+    ("indigo", 0x4b0082ff),
+    ("ivory", 0xfffff0ff),
+    ("khaki", 0xf0e68cff),
+    ("lavender", 0xe6e6faff),
+    ("lavenderblush", 0xfff0f5ff),
+    ("lawngreen", 0x7cfc00ff),
+    ("lemonchiffon", 0xfffacdff),
+    ("lightblue", 0xadd8e6ff),
+    ("lightcoral", 0xf08080ff),
+    ("lightcyan", 0xe0ffffff),
+    ("lightgoldenrodyellow", 0xfafad2ff),
+    ("lightgray", 0xd3d3d3ff),
+    ("lightgreen", 0x90ee90ff),
+    ("lightgrey", 0xd3d3d3ff),
+    ("lightpink", 0xffb6c1ff),
+    ("lightsalmon", 0xffa07aff),
+    ("lightseagreen", 0x20b2aaff),
+    ("lightskyblue", 0x87cefaff),
+    ("lightslategray", 0x778899ff),
+    ("lightslategrey", 0x778899ff),
+    ("lightsteelblue", 0xb0c4deff),
+    ("lightyellow", 0xffffe0ff),
+    ("lime", 0x00ff00ff),
+    ("limegreen", 0x32cd32ff),
+    ("linen", 0xfaf0e6ff),
+    ("magenta", 0xff00ffff),
+    ("maroon", 0x800000ff),
+    ("mediumaquamarine", 0x66cdaaff),
+    ("mediumblue", 0x0000cdff),
+    ("mediumorchid", 0xba55d3ff),
+    ("mediumpurple", 0x9370dbff),
+    ("mediumseagreen", 0x3cb371ff),
+    ("mediumslateblue", 0x7b68eeff),
+    ("mediumspringgreen", 0x00fa9aff),
+    ("mediumturquoise", 0x48d1ccff),
+    ("mediumvioletred", 0xc71585ff),
+    ("midnightblue", 0x191970ff),
+    ("mintcream", 0xf5fffaff),
+    ("mistyrose", 0xffe4e1ff),
+    ("moccasin", 0xffe4b5ff),
+    ("navajowhite", 0xffdeadff),
+    ("navy", 0x000080ff),
+    ("oldlace", 0xfdf5e6ff),
+    ("olive", 0x808000ff),
+    ("olivedrab", 0x6b8e23ff),
+    ("orange", 0xffa500ff),
+    ("orangered", 0xff4500ff),
+    ("orchid", 0xda70d6ff),
+    ("palegoldenrod", 0xeee8aaff),
+    ("palegreen", 0x98fb98ff),
+    ("paleturquoise", 0xafeeeeff),
+    ("palevioletred", 0xdb7093ff),
+    ("papayawhip", 0xffefd5ff),
+    ("peachpuff", 0xffdab9ff),
+    ("peru", 0xcd853fff),
+    ("pink", 0xffc0cbff),
+    ("plum", 0xdda0ddff),
+    ("powderblue", 0xb0e0e6ff),
+    ("purple", 0x800080ff),
+    ("rebeccapurple", 0x663399ff),
+    ("red", 0xff0000ff),
+    ("rosybrown", 0xbc8f8fff),
+    ("royalblue", 0x4169e1ff),
+    ("saddlebrown", 0x8b4513ff),
+    ("salmon", 0xfa8072ff),
+    ("sandybrown", 0xf4a460ff),
+    ("seagreen", 0x2e8b57ff),
+    ("seashell", 0xfff5eeff),
+    ("sienna", 0xa0522dff),
+    ("silver", 0xc0c0c0ff),
+    ("skyblue", 0x87ceebff),
+    ("slateblue", 0x6a5acdff),
+    ("slategray", 0x708090ff),
+    ("slategrey", 0x708090ff),
+    ("snow", 0xfffafaff),
+    ("springgreen", 0x00ff7fff),
+    ("steelblue", 0x4682b4ff),
+    ("tan", 0xd2b48cff),
+    ("teal", 0x008080ff),
+    ("thistle", 0xd8bfd8ff),
+    ("tomato", 0xff6347ff),
+    ("turquoise", 0x40e0d0ff),
+    ("violet", 0xee82eeff),
+    ("wheat", 0xf5deb3ff),
+    ("white", 0xffffffff),
+    ("whitesmoke", 0xf5f5f5ff),
+    ("yellow", 0xffff00ff),
+    ("yellowgreen", 0x9acd32ff),
+    ("transparent", 0x00000000),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLORS: [[f64; 4]; 5] = [
+        [0.0, 0.0, 0.0, 1.0],
+        [1.0, 1.0, 1.0, 1.0],
+        [0.8, 0.2, 0.05, 1.0],
+        [0.1, 0.6, 0.3, 0.5],
+        [0.25, 0.25, 0.9, 0.0],
+    ];
+
+    fn assert_close(actual: [f64; 4], expected: [f64; 4]) {
+        for (actual_value, expected_value) in actual.iter().zip(expected) {
+            assert!(
+                (actual_value - expected_value).abs() < 1e-6,
+                "{actual:?} != {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn hex_round_trips() {
+        for hex in ["#000000", "#ffffff", "#ff8000", "#12345678", "#663399"] {
+            assert_eq!(Color::hex(hex).unwrap().to_hex(), hex);
+        }
+
+        assert_eq!(Color::hex("#f80").unwrap().to_hex(), "#ff8800");
+        assert_eq!(Color::hex("f808").unwrap().to_hex(), "#ff880088");
+        assert!(Color::hex("#ff80").is_ok());
+        assert!(Color::hex("#ff800").is_err());
+        assert!(Color::hex("#gg0000").is_err());
+    }
+
+    #[test]
+    fn rgba8_round_trips() {
+        for rgba in [
+            [0, 0, 0, 255],
+            [255, 128, 0, 255],
+            [1, 2, 3, 4],
+            [200, 100, 50, 0],
+        ] {
+            assert_eq!(Color::from_rgba8(rgba).to_rgba8(), rgba);
+            assert_eq!(<[u8; 4]>::from(Color::from(rgba)), rgba);
+        }
+    }
+
+    #[test]
+    fn hsv_round_trips() {
+        for rgba in COLORS {
+            let [h, s, v, a] = Color::from(rgba).to_hsv();
+            assert_close(Color::hsv(h, s, v, a).to_linear(), rgba);
+        }
+
+        assert_close(
+            Color::hsv(120.0, 1.0, 1.0, 1.0).to_linear(),
+            [0.0, 1.0, 0.0, 1.0],
+        );
+    }
+
+    #[test]
+    fn hsl_round_trips() {
+        for rgba in COLORS {
+            let [h, s, l, a] = Color::from(rgba).to_hsl();
+            assert_close(Color::hsl(h, s, l, a).to_linear(), rgba);
+        }
+
+        assert_close(
+            Color::hsl(240.0, 1.0, 0.5, 1.0).to_linear(),
+            [0.0, 0.0, 1.0, 1.0],
+        );
+    }
+
+    #[test]
+    fn oklab_round_trips() {
+        for rgba in COLORS {
+            let [l, a, b, alpha] = Color::from(rgba).to_oklab();
+            assert_close(Color::oklab(l, a, b, alpha).to_linear(), rgba);
+        }
+
+        assert_close(
+            Color::linear(1.0, 1.0, 1.0, 1.0).to_oklab(),
+            [1.0, 0.0, 0.0, 1.0],
+        );
+    }
+
+    #[test]
+    fn oklch_round_trips() {
+        for rgba in COLORS {
+            let [l, c, h, a] = Color::from(rgba).to_oklch();
+            assert_close(Color::oklch(l, c, h, a).to_linear(), rgba);
+        }
+    }
+
+    #[test]
+    fn named_colors_round_trip() {
+        for (name, rgba) in NAMED_COLORS {
+            let color = Color::named(name).unwrap();
+            assert_eq!(color.to_rgba8(), rgba.to_be_bytes());
+
+            // some colors have two names, either one finds the same color again.
+            let found = color.name().unwrap();
+            assert_eq!(Color::named(found), Some(color));
+        }
+
+        assert_eq!(Color::named("RebeccaPurple"), Color::named("rebeccapurple"));
+        assert_eq!(
+            "rebeccapurple".parse::<Color>().unwrap().name(),
+            Some("rebeccapurple")
+        );
+        assert_eq!(Color::named("not a color"), None);
+    }
+
+    #[test]
+    fn blends_opaque_colors() {
+        let src = Color::linear(0.25, 0.5, 0.75, 1.0);
+        let dst = Color::linear(0.5, 0.25, 0.5, 1.0);
+
+        for (mode, expected) in [
+            (BlendMode::Over, [0.25, 0.5, 0.75, 1.0]),
+            (BlendMode::Add, [0.75, 0.75, 1.25, 1.0]),
+            (BlendMode::Multiply, [0.125, 0.125, 0.375, 1.0]),
+            (BlendMode::Screen, [0.625, 0.625, 0.875, 1.0]),
+        ] {
+            assert_close(src.blend(dst, mode).to_linear(), expected);
+        }
+    }
+
+    #[test]
+    fn blends_translucent_colors() {
+        let red = Color::linear(1.0, 0.0, 0.0, 0.5);
+        let blue = Color::linear(0.0, 0.0, 1.0, 1.0);
+        assert_close(
+            red.blend(blue, BlendMode::Over).to_linear(),
+            [0.5, 0.0, 0.5, 1.0],
+        );
+
+        // the mode only applies where both colors cover the pixel.
+        let gray = Color::linear(0.5, 0.5, 0.5, 0.5);
+        let dst = Color::linear(1.0, 0.0, 0.5, 1.0);
+        assert_close(
+            gray.blend(dst, BlendMode::Multiply).to_linear(),
+            [0.75, 0.0, 0.375, 1.0],
+        );
+
+        let half = Color::linear(0.2, 0.4, 0.6, 0.5);
+        let transparent = Color::linear(0.9, 0.9, 0.9, 0.0);
+        for mode in [
+            BlendMode::Over,
+            BlendMode::Add,
+            BlendMode::Multiply,
+            BlendMode::Screen,
+        ] {
+            assert_close(transparent.blend(half, mode).to_linear(), half.to_linear());
+            assert_close(half.blend(transparent, mode).to_linear(), half.to_linear());
+            assert_close(
+                transparent.blend(transparent, mode).to_linear(),
+                [0.0, 0.0, 0.0, 0.0],
+            );
+        }
+    }
+
+    #[test]
+    fn lerps_in_each_space() {
+        let black = Color::linear(0.0, 0.0, 0.0, 1.0);
+        let white = Color::linear(1.0, 1.0, 1.0, 0.0);
+
+        for lerp in [Color::lerp, Color::lerp_srgb, Color::lerp_oklab] {
+            assert_close(lerp(black, white, 0.0).to_linear(), black.to_linear());
+            assert_close(lerp(black, white, 1.0).to_linear(), white.to_linear());
+        }
+
+        assert_close(black.lerp(white, 0.5).to_linear(), [0.5, 0.5, 0.5, 0.5]);
+        assert_close(black.lerp_srgb(white, 0.5).to_srgb(), [0.5, 0.5, 0.5, 0.5]);
+        assert_close(
+            black.lerp_oklab(white, 0.5).to_linear(),
+            [0.125, 0.125, 0.125, 0.5],
+        );
+
+        // the perceived lightness changes evenly, even between very different hues.
+        let red = Color::linear(1.0, 0.0, 0.0, 1.0);
+        let blue = Color::linear(0.0, 0.0, 1.0, 1.0);
+        let middle = red.lerp_oklab(blue, 0.25).to_oklab()[0];
+        assert!((middle - (0.75 * red.to_oklab()[0] + 0.25 * blue.to_oklab()[0])).abs() < 1e-6);
+    }
+
+    #[test]
+    fn premultiplies_alpha() {
+        let color = Color::linear(0.8, 0.4, 0.2, 0.5);
+        assert_close(color.premultiplied().to_linear(), [0.4, 0.2, 0.1, 0.5]);
+        assert_close(
+            color.premultiplied().unpremultiplied().to_linear(),
+            color.to_linear(),
+        );
+
+        for rgba in COLORS.into_iter().filter(|rgba| rgba[3] > 0.0) {
+            let color = Color::from(rgba);
+            assert_close(color.premultiplied().unpremultiplied().to_linear(), rgba);
+        }
+
+        let transparent = Color::linear(0.25, 0.25, 0.9, 0.0);
+        assert_close(
+            transparent.premultiplied().to_linear(),
+            [0.0, 0.0, 0.0, 0.0],
+        );
+        assert_close(
+            transparent.unpremultiplied().to_linear(),
+            [0.0, 0.0, 0.0, 0.0],
+        );
+    }
+
+    #[test]
+    fn contrast_matches_wcag() {
+        let black = Color::hex("#000000").unwrap();
+        let white = Color::hex("#ffffff").unwrap();
+
+        assert!(black.luminance().abs() < 1e-6);
+        assert!((white.luminance() - 1.0).abs() < 1e-6);
+        assert!((Color::hex("#ff0000").unwrap().luminance() - 0.2126).abs() < 1e-6);
+        assert!((Color::hex("#00ff00").unwrap().luminance() - 0.7152).abs() < 1e-6);
+        assert!((Color::hex("#0000ff").unwrap().luminance() - 0.0722).abs() < 1e-6);
+
+        assert!((black.contrast(white) - 21.0).abs() < 1e-6);
+        assert!((white.contrast(black) - 21.0).abs() < 1e-6);
+        assert!((white.contrast(white) - 1.0).abs() < 1e-6);
+
+        // #767676 is the lightest gray that passes AA (4.5:1) on white, #777777 isn't.
+        let passing = Color::hex("#767676").unwrap().contrast(white);
+        let failing = Color::hex("#777777").unwrap().contrast(white);
+        assert!((passing - 4.54).abs() < 0.01, "{passing}");
+        assert!(passing >= 4.5 && failing < 4.5, "{passing} {failing}");
+
+        assert_eq!(white.contrasting(), black);
+        assert_eq!(black.contrasting(), white);
+    }
+
+    #[cfg(feature = "color-serde")]
+    #[test]
+    fn serde_round_trips() {
+        #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+        struct Theme {
+            color: Color,
+        }
+
+        for rgba in COLORS.into_iter().chain([[0.123456789, 1.5, -0.25, 0.3]]) {
+            let theme = Theme {
+                color: Color::from(rgba),
+            };
+
+            let text = toml::to_string(&theme).unwrap();
+            assert_eq!(toml::from_str::<Theme>(&text).unwrap(), theme);
+
+            let text = ron::to_string(&theme).unwrap();
+            assert_eq!(ron::from_str::<Theme>(&text).unwrap(), theme);
+        }
+
+        let theme = toml::from_str::<Theme>("color = \"#ff8000\"").unwrap();
+        assert_eq!(theme.color, Color::hex("#ff8000").unwrap());
+
+        let theme = toml::from_str::<Theme>("color = \"tomato\"").unwrap();
+        assert_eq!(theme.color, Color::named("tomato").unwrap());
+    }
+}
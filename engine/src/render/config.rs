@@ -0,0 +1,319 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::error::EngineError;
+
+/// Adapter, device, surface and window settings used by [`run`](super::framework::run).
+///
+/// Can be loaded from a `.toml` or `.ron` file, every field is optional there. Features are named
+/// like their [`wgpu::Features`] constant, e.g. `"DEPTH_CLIP_CONTROL"`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EngineConfig {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    /// Backends to pick an adapter from, all of them if empty.
+    pub backends: Vec<Backend>,
+    pub power_preference: PowerPreference,
+    pub force_fallback_adapter: bool,
+    /// Features the device can't be created without.
+    pub required_features: Vec<String>,
+    /// Features that are requested only if the adapter supports them.
+    pub optional_features: Vec<String>,
+    pub limits: Limits,
+    /// Present modes in order of preference, the first one the surface supports is used.
+    pub present_modes: Vec<PresentMode>,
+    pub surface_format: SurfaceFormat,
+    pub alpha_mode: AlphaMode,
+    /// Samples per pixel, anything above 1 renders into a multisampled target first. Startup fails
+    /// if the surface or depth format doesn't support this count.
+    pub sample_count: u32,
+    /// Format of the framework-managed depth target, or no depth target at all.
+    pub depth_format: Option<DepthFormat>,
+    /// Seconds between logging a [`FrameStats`](super::stats::FrameStats) summary, or never.
+    pub stats_log_interval: Option<f64>,
+    /// Measures profiler scopes with timestamp queries, if the adapter supports them.
+    pub gpu_profiling: bool,
+    /// Stops updating and rendering while the window is unfocused.
+    pub pause_when_unfocused: bool,
+    /// Records input and fixed-step ticks into this file, see [`Recording`](super::replay::Recording).
+    pub record: Option<PathBuf>,
+    /// Replays a recording made with `record` instead of taking input, until it's over.
+    pub replay: Option<PathBuf>,
+    /// Threads of the [`JobSystem`](super::jobs::JobSystem), one for every core but one if unset.
+    pub job_threads: Option<usize>,
+    /// Milliseconds per frame the main thread may spend uploading finished jobs to the GPU.
+    pub upload_budget_ms: Option<f64>,
+    /// Bytes per frame the main thread may upload from finished jobs.
+    pub upload_budget_bytes: Option<u64>,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            title: "Engine".to_string(),
+            width: 1280,
+            height: 720,
+            backends: Vec::new(),
+            power_preference: PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            required_features: Vec::new(),
+            // `PipelineBuilder` uses unclipped depth where it's supported.
+            optional_features: vec!["DEPTH_CLIP_CONTROL".to_string()],
+            limits: Limits::Default,
+            present_modes: vec![PresentMode::AutoVsync],
+            surface_format: SurfaceFormat::Srgb,
+            alpha_mode: AlphaMode::Auto,
+            sample_count: 1,
+            depth_format: Some(DepthFormat::Depth32Float),
+            stats_log_interval: None,
+            gpu_profiling: false,
+            pause_when_unfocused: false,
+            record: None,
+            replay: None,
+            job_threads: None,
+            upload_budget_ms: Some(2.0),
+            upload_budget_bytes: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Backend {
+    Vulkan,
+    Metal,
+    Dx12,
+    Dx11,
+    Gl,
+    BrowserWebGpu,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerPreference {
+    LowPower,
+    HighPerformance,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Limits {
+    Default,
+    DownlevelDefaults,
+    DownlevelWebgl2,
+    /// Everything the adapter supports.
+    Adapter,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresentMode {
+    AutoVsync,
+    AutoNoVsync,
+    Fifo,
+    FifoRelaxed,
+    Immediate,
+    Mailbox,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SurfaceFormat {
+    /// Prefer an sRGB format, so shaders can output linear colors.
+    Srgb,
+    /// Prefer a non-sRGB format.
+    Linear,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlphaMode {
+    Auto,
+    Opaque,
+    PreMultiplied,
+    PostMultiplied,
+    Inherit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DepthFormat {
+    Depth16Unorm,
+    Depth24Plus,
+    Depth24PlusStencil8,
+    Depth32Float,
+}
+
+impl EngineConfig {
+    /// Loads a config from a `.toml` or `.ron` file, depending on its extension.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        load_file(path)
+    }
+
+    /// Like [`EngineConfig::load`], but falls back to the default config if `path` doesn't exist.
+    pub fn load_or_default(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        match path.as_ref().exists() {
+            true => Self::load(path),
+            false => Ok(Self::default()),
+        }
+    }
+
+    pub fn backends(&self) -> wgpu::Backends {
+        if self.backends.is_empty() {
+            return wgpu::Backends::all();
+        }
+
+        self.backends
+            .iter()
+            .fold(wgpu::Backends::empty(), |backends, backend| {
+                backends
+                    | match backend {
+                        Backend::Vulkan => wgpu::Backends::VULKAN,
+                        Backend::Metal => wgpu::Backends::METAL,
+                        Backend::Dx12 => wgpu::Backends::DX12,
+                        Backend::Dx11 => wgpu::Backends::DX11,
+                        Backend::Gl => wgpu::Backends::GL,
+                        Backend::BrowserWebGpu => wgpu::Backends::BROWSER_WEBGPU,
+                    }
+            })
+    }
+
+    pub fn power_preference(&self) -> wgpu::PowerPreference {
+        match self.power_preference {
+            PowerPreference::LowPower => wgpu::PowerPreference::LowPower,
+            PowerPreference::HighPerformance => wgpu::PowerPreference::HighPerformance,
+        }
+    }
+
+    /// The features to request from `adapter`, failing if a required one isn't supported.
+    pub fn features(&self, adapter: &wgpu::Adapter) -> Result<wgpu::Features, EngineError> {
+        let required = parse_features(&self.required_features)?;
+        let mut optional = parse_features(&self.optional_features)?;
+
+        if self.gpu_profiling {
+            optional |= wgpu::Features::TIMESTAMP_QUERY;
+        }
+
+        let supported = adapter.features();
+
+        if !supported.contains(required) {
+            return Err(EngineError::MissingFeatures(required - supported));
+        }
+
+        Ok(required | (optional & supported))
+    }
+
+    pub fn limits(&self, adapter: &wgpu::Adapter) -> wgpu::Limits {
+        match self.limits {
+            Limits::Default => wgpu::Limits::default(),
+            Limits::DownlevelDefaults => wgpu::Limits::downlevel_defaults(),
+            Limits::DownlevelWebgl2 => wgpu::Limits::downlevel_webgl2_defaults(),
+            Limits::Adapter => adapter.limits(),
+        }
+    }
+
+    /// Picks the first preferred present mode that is supported, or the surface's first one.
+    pub fn present_mode(&self, supported: &[wgpu::PresentMode]) -> Option<wgpu::PresentMode> {
+        self.present_modes
+            .iter()
+            .map(|mode| match mode {
+                PresentMode::AutoVsync => wgpu::PresentMode::AutoVsync,
+                PresentMode::AutoNoVsync => wgpu::PresentMode::AutoNoVsync,
+                PresentMode::Fifo => wgpu::PresentMode::Fifo,
+                PresentMode::FifoRelaxed => wgpu::PresentMode::FifoRelaxed,
+                PresentMode::Immediate => wgpu::PresentMode::Immediate,
+                PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+            })
+            .find(|mode| {
+                // the auto modes are resolved by wgpu itself, and always work.
+                matches!(
+                    mode,
+                    wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync
+                ) || supported.contains(mode)
+            })
+            .or_else(|| supported.first().copied())
+    }
+
+    pub fn surface_format(&self, supported: &[wgpu::TextureFormat]) -> Option<wgpu::TextureFormat> {
+        let srgb = self.surface_format == SurfaceFormat::Srgb;
+
+        supported
+            .iter()
+            .copied()
+            .find(|format| format.describe().srgb == srgb)
+            .or_else(|| supported.first().copied())
+    }
+
+    pub fn depth_format(&self) -> Option<wgpu::TextureFormat> {
+        self.depth_format.map(|format| match format {
+            DepthFormat::Depth16Unorm => wgpu::TextureFormat::Depth16Unorm,
+            DepthFormat::Depth24Plus => wgpu::TextureFormat::Depth24Plus,
+            DepthFormat::Depth24PlusStencil8 => wgpu::TextureFormat::Depth24PlusStencil8,
+            DepthFormat::Depth32Float => wgpu::TextureFormat::Depth32Float,
+        })
+    }
+
+    pub fn alpha_mode(
+        &self,
+        supported: &[wgpu::CompositeAlphaMode],
+    ) -> Option<wgpu::CompositeAlphaMode> {
+        let mode = match self.alpha_mode {
+            AlphaMode::Auto => wgpu::CompositeAlphaMode::Auto,
+            AlphaMode::Opaque => wgpu::CompositeAlphaMode::Opaque,
+            AlphaMode::PreMultiplied => wgpu::CompositeAlphaMode::PreMultiplied,
+            AlphaMode::PostMultiplied => wgpu::CompositeAlphaMode::PostMultiplied,
+            AlphaMode::Inherit => wgpu::CompositeAlphaMode::Inherit,
+        };
+
+        match mode == wgpu::CompositeAlphaMode::Auto || supported.contains(&mode) {
+            true => Some(mode),
+            false => supported.first().copied(),
+        }
+    }
+}
+
+/// Reads a `.toml` or `.ron` file, depending on its extension.
+pub(crate) fn load_file<T: DeserializeOwned>(path: impl AsRef<Path>) -> anyhow::Result<T> {
+    let path = path.as_ref();
+    let contents =
+        std::fs::read_to_string(path).with_context(|| format!("unable to read {path:?}"))?;
+
+    let value = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&contents)?,
+        Some("ron") => ron::from_str(&contents)?,
+        _ => bail!("{path:?} is neither a .toml nor a .ron file"),
+    };
+
+    Ok(value)
+}
+
+/// Writes a `.toml` or `.ron` file, depending on its extension.
+pub(crate) fn save_file<T: Serialize>(value: &T, path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let path = path.as_ref();
+
+    let contents = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::to_string_pretty(value)?,
+        Some("ron") => ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?,
+        _ => bail!("{path:?} is neither a .toml nor a .ron file"),
+    };
+
+    std::fs::write(path, contents).with_context(|| format!("unable to write {path:?}"))
+}
+
+/// Looks features up by the name of their constant, as that's what their `Debug` output is.
+fn parse_features(names: &[String]) -> Result<wgpu::Features, EngineError> {
+    names
+        .iter()
+        .try_fold(wgpu::Features::empty(), |features, name| {
+            let feature = (0..u64::BITS)
+                .filter_map(|bit| wgpu::Features::from_bits(1 << bit))
+                .find(|feature| format!("{feature:?}") == *name)
+                .ok_or_else(|| EngineError::UnknownFeature(name.clone()))?;
+
+            Ok(features | feature)
+        })
+}
@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+
+use std::time::Duration;
+
+use super::{
+    config::EngineConfig,
+    input::Input,
+    jobs::{CancelToken, JobSystem, Priority},
+    profiler::GpuProfiler,
+    stats::FrameStats,
+    time::Time,
+    upload::{Upload, UploadBudget, UploadHandle, UploadQueue},
+    window::{WindowHandle, WindowId, WindowOptions},
+};
+
+/// Engine state maintained by the framework runners, and handed to the [`Framework`](super::framework::Framework) hooks.
+pub struct Context {
+    pub(crate) time: Time,
+    pub(crate) stats: FrameStats,
+    pub(crate) profiler: GpuProfiler,
+    pub(crate) input: Input,
+    pub(crate) jobs: JobSystem,
+    pub(crate) uploads: UploadQueue,
+    pub(crate) window: WindowHandle,
+    pub(crate) windows: BTreeMap<WindowId, WindowHandle>,
+    pub(crate) opening: Vec<(WindowId, WindowOptions)>,
+    pub(crate) closing: Vec<WindowId>,
+    next_window_id: u64,
+}
+
+impl Context {
+    pub fn new(time: Time) -> Self {
+        Self {
+            time,
+            stats: FrameStats::default(),
+            profiler: GpuProfiler::disabled(),
+            input: Input::default(),
+            jobs: JobSystem::default(),
+            uploads: UploadQueue::default(),
+            window: WindowHandle::headless(),
+            windows: BTreeMap::new(),
+            opening: Vec::new(),
+            closing: Vec::new(),
+            next_window_id: 1,
+        }
+    }
+
+    /// Sets up the job system and upload budget from `config`.
+    pub(crate) fn configure(&mut self, config: &EngineConfig) {
+        if let Some(threads) = config.job_threads {
+            self.jobs = JobSystem::new(threads);
+        }
+
+        self.uploads.set_budget(UploadBudget {
+            time: config
+                .upload_budget_ms
+                .map(|ms| Duration::from_secs_f64(ms / 1000.0)),
+            bytes: config.upload_budget_bytes,
+        });
+    }
+
+    pub fn time(&self) -> &Time {
+        &self.time
+    }
+
+    pub fn stats(&self) -> &FrameStats {
+        &self.stats
+    }
+
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
+    /// Mutable access to the input state, e.g. for rebinding actions.
+    pub fn input_mut(&mut self) -> &mut Input {
+        &mut self.input
+    }
+
+    /// The thread pool for CPU heavy work, like generating meshes or decoding assets.
+    pub fn jobs(&self) -> &JobSystem {
+        &self.jobs
+    }
+
+    pub fn uploads(&self) -> &UploadQueue {
+        &self.uploads
+    }
+
+    pub fn uploads_mut(&mut self) -> &mut UploadQueue {
+        &mut self.uploads
+    }
+
+    /// Runs `job` on the job system, and uploads its result on the main thread once it's done.
+    pub fn spawn_upload<T, F>(&mut self, priority: Priority, job: F) -> UploadHandle<T::Output>
+    where
+        T: Upload,
+        F: FnOnce(&CancelToken) -> T + Send + 'static,
+    {
+        let job = self.jobs.spawn(priority, job);
+        self.uploads.push(job)
+    }
+
+    pub fn window(&self) -> &WindowHandle {
+        &self.window
+    }
+
+    /// Cursor, fullscreen and title control, and [`WindowHandle::request_exit`].
+    pub fn window_mut(&mut self) -> &mut WindowHandle {
+        &mut self.window
+    }
+
+    /// Opens another window sharing the device, before the next frame. It's rendered through
+    /// [`Framework::render_window`](super::framework::Framework::render_window).
+    ///
+    /// Headless runs never open any windows.
+    pub fn open_window(&mut self, options: WindowOptions) -> WindowId {
+        let id = WindowId(self.next_window_id);
+        self.next_window_id += 1;
+
+        self.opening.push((id, options));
+        id
+    }
+
+    /// Closes a window before the next frame, closing the main window exits the event loop.
+    pub fn close_window(&mut self, id: WindowId) {
+        match id {
+            WindowId::MAIN => self.window.request_exit(),
+            id => self.closing.push(id),
+        }
+    }
+
+    pub fn window_by_id(&self, id: WindowId) -> Option<&WindowHandle> {
+        match id {
+            WindowId::MAIN => Some(&self.window),
+            id => self.windows.get(&id),
+        }
+    }
+
+    pub fn window_by_id_mut(&mut self, id: WindowId) -> Option<&mut WindowHandle> {
+        match id {
+            WindowId::MAIN => Some(&mut self.window),
+            id => self.windows.get_mut(&id),
+        }
+    }
+
+    /// Every open window, starting with the main one.
+    pub fn windows(&self) -> impl Iterator<Item = (WindowId, &WindowHandle)> {
+        std::iter::once((WindowId::MAIN, &self.window))
+            .chain(self.windows.iter().map(|(id, window)| (*id, window)))
+    }
+
+    pub(crate) fn window_id(&self, winit_id: winit::window::WindowId) -> Option<WindowId> {
+        self.windows()
+            .find(|(_, window)| window.winit().map(|window| window.id()) == Some(winit_id))
+            .map(|(id, _)| id)
+    }
+
+    /// The GPU profiler, which only measures anything if
+    /// [`EngineConfig::gpu_profiling`](super::config::EngineConfig::gpu_profiling) is enabled.
+    pub fn profiler(&mut self) -> &mut GpuProfiler {
+        &mut self.profiler
+    }
+
+    /// Records the frame that just finished, and starts timing the next one.
+    pub(crate) fn end_frame(&mut self) {
+        self.stats.record_frame(self.time.time_delta());
+        self.time.post_update();
+        self.input.end_frame();
+    }
+}
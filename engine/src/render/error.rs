@@ -0,0 +1,86 @@
+use std::fmt;
+
+use super::app::Stage;
+
+/// Everything that can stop the engine from starting, or from continuing to render.
+#[derive(Debug)]
+pub enum EngineError {
+    CreateWindow(winit::error::OsError),
+    CreateSurface(wgpu::CreateSurfaceError),
+    /// No adapter could be found, not even a fallback one.
+    NoAdapter,
+    RequestDevice(wgpu::RequestDeviceError),
+    /// A feature name in the [`EngineConfig`](super::config::EngineConfig) isn't a [`wgpu::Features`] constant.
+    UnknownFeature(String),
+    /// The adapter lacks some of the required features.
+    MissingFeatures(wgpu::Features),
+    /// The surface doesn't support any format or present mode with the chosen adapter.
+    UnsupportedSurface,
+    /// The color or depth format can't be multisampled this many times.
+    UnsupportedSampleCount(u32, wgpu::TextureFormat),
+    /// The framework's [`TickRate`](super::time::TickRate) isn't valid, e.g. zero ticks per second.
+    InvalidTickRate(f64),
+    /// The GPU ran out of memory while acquiring a frame.
+    OutOfMemory,
+    /// [`Framework::init`](super::framework::Framework::init) or a startup system failed.
+    Init(anyhow::Error),
+    /// The `before`/`after` constraints of these systems contradict each other.
+    SystemCycle(Stage, Vec<String>),
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::CreateWindow(err) => write!(f, "unable to create window: {err}"),
+            EngineError::CreateSurface(err) => write!(f, "unable to create surface: {err}"),
+            EngineError::NoAdapter => write!(f, "no suitable graphics adapter found"),
+            EngineError::RequestDevice(err) => write!(f, "unable to request device: {err}"),
+            EngineError::UnknownFeature(name) => write!(f, "unknown feature {name:?}"),
+            EngineError::MissingFeatures(features) => {
+                write!(f, "adapter doesn't support required features {features:?}")
+            }
+            EngineError::UnsupportedSurface => {
+                write!(f, "surface is not supported by the graphics adapter")
+            }
+            EngineError::UnsupportedSampleCount(count, format) => {
+                write!(f, "{count}x multisampling isn't supported for {format:?}")
+            }
+            EngineError::InvalidTickRate(hz) => write!(f, "{hz}hz isn't a valid tick rate"),
+            EngineError::OutOfMemory => write!(f, "graphics device ran out of memory"),
+            EngineError::Init(err) => write!(f, "framework failed to initialize: {err:#}"),
+            EngineError::SystemCycle(stage, systems) => {
+                write!(f, "systems {systems:?} in {stage:?} are ordered in a cycle")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EngineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EngineError::CreateWindow(err) => Some(err),
+            EngineError::CreateSurface(err) => Some(err),
+            EngineError::RequestDevice(err) => Some(err),
+            EngineError::Init(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<winit::error::OsError> for EngineError {
+    fn from(err: winit::error::OsError) -> Self {
+        EngineError::CreateWindow(err)
+    }
+}
+
+impl From<wgpu::CreateSurfaceError> for EngineError {
+    fn from(err: wgpu::CreateSurfaceError) -> Self {
+        EngineError::CreateSurface(err)
+    }
+}
+
+impl From<wgpu::RequestDeviceError> for EngineError {
+    fn from(err: wgpu::RequestDeviceError) -> Self {
+        EngineError::RequestDevice(err)
+    }
+}
@@ -0,0 +1,233 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{bail, Context as _};
+use glam::Vec3;
+
+use super::mesh::Mesh;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PlyFormat {
+    Ascii,
+    #[default]
+    BinaryLittleEndian,
+}
+
+/// Writing meshes to files other tools open, e.g. to look at what a mesher produced in Blender.
+impl Mesh {
+    /// Writes the mesh to `path` as OBJ, binary PLY or binary STL, picked by its extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+
+        let write: fn(&Mesh, &mut BufWriter<File>) -> io::Result<()> = match extension.as_deref() {
+            Some("obj") => |mesh, writer| mesh.write_obj(writer),
+            Some("ply") => |mesh, writer| mesh.write_ply(writer, PlyFormat::BinaryLittleEndian),
+            Some("stl") => |mesh, writer| mesh.write_stl(writer),
+            _ => bail!("{path:?} isn't an .obj, .ply or .stl file"),
+        };
+
+        let file = File::create(path).with_context(|| format!("unable to create {path:?}"))?;
+        let mut writer = BufWriter::new(file);
+
+        write(self, &mut writer)
+            .and_then(|_| writer.flush())
+            .with_context(|| format!("unable to write {path:?}"))
+    }
+
+    /// Writes the mesh as a Wavefront OBJ file, with a normal for every vertex.
+    pub fn write_obj(&self, mut writer: impl Write) -> io::Result<()> {
+        for vertex in &self.vertices {
+            let [x, y, z] = vertex.position;
+            writeln!(writer, "v {x} {y} {z}")?;
+        }
+
+        for vertex in &self.vertices {
+            let [x, y, z] = vertex.normal;
+            writeln!(writer, "vn {x} {y} {z}")?;
+        }
+
+        for triangle in self.triangle_indices().chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|idx| idx + 1);
+            writeln!(writer, "f {a}//{a} {b}//{b} {c}//{c}")?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the mesh as a PLY file, with positions and normals for every vertex.
+    pub fn write_ply(&self, mut writer: impl Write, format: PlyFormat) -> io::Result<()> {
+        let triangles = self.triangle_indices();
+
+        let format_name = match format {
+            PlyFormat::Ascii => "ascii",
+            PlyFormat::BinaryLittleEndian => "binary_little_endian",
+        };
+
+        writeln!(writer, "ply")?;
+        writeln!(writer, "format {format_name} 1.0")?;
+        writeln!(writer, "element vertex {}", self.vertices.len())?;
+
+        for property in ["x", "y", "z", "nx", "ny", "nz"] {
+            writeln!(writer, "property float {property}")?;
+        }
+
+        writeln!(writer, "element face {}", triangles.len() / 3)?;
+        writeln!(writer, "property list uchar uint vertex_indices")?;
+        writeln!(writer, "end_header")?;
+
+        match format {
+            PlyFormat::Ascii => {
+                for vertex in &self.vertices {
+                    let [x, y, z] = vertex.position;
+                    let [nx, ny, nz] = vertex.normal;
+                    writeln!(writer, "{x} {y} {z} {nx} {ny} {nz}")?;
+                }
+
+                for triangle in triangles.chunks_exact(3) {
+                    writeln!(writer, "3 {} {} {}", triangle[0], triangle[1], triangle[2])?;
+                }
+            }
+            PlyFormat::BinaryLittleEndian => {
+                for vertex in &self.vertices {
+                    for value in vertex.position.iter().chain(&vertex.normal) {
+                        writer.write_all(&value.to_le_bytes())?;
+                    }
+                }
+
+                for triangle in triangles.chunks_exact(3) {
+                    writer.write_all(&[3])?;
+
+                    for idx in triangle {
+                        writer.write_all(&idx.to_le_bytes())?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the mesh as a binary STL file. STL only has a normal per triangle, which is
+    /// worked out from the triangle's winding. Fails on indices past the last vertex, as the
+    /// positions are written in place of them.
+    pub fn write_stl(&self, mut writer: impl Write) -> io::Result<()> {
+        let triangles = self.triangle_indices();
+
+        let mut header = [0; 80];
+        let comment = b"binary STL exported by engine";
+        header[..comment.len()].copy_from_slice(comment);
+
+        writer.write_all(&header)?;
+        writer.write_all(&(triangles.len() as u32 / 3).to_le_bytes())?;
+
+        for triangle in triangles.chunks_exact(3) {
+            let mut positions = [Vec3::ZERO; 3];
+
+            for (position, idx) in positions.iter_mut().zip(triangle) {
+                let vertex = self.vertices.get(*idx as usize).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "index {idx} is out of bounds of {} vertices",
+                            self.vertices.len()
+                        ),
+                    )
+                })?;
+
+                *position = Vec3::from(vertex.position);
+            }
+
+            let [a, b, c] = positions;
+            let normal = (b - a).cross(c - a).normalize_or_zero();
+
+            for point in [normal, a, b, c] {
+                for value in point.to_array() {
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+            }
+
+            // the attribute byte count, which nothing uses.
+            writer.write_all(&0u16.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{obj::ObjModel, vertex::Vertex};
+
+    fn round_trip_obj(mesh: &Mesh) {
+        let mut obj = Vec::new();
+        mesh.write_obj(&mut obj).unwrap();
+
+        let model = ObjModel::parse(std::str::from_utf8(&obj).unwrap()).unwrap();
+        assert_eq!(model.meshes.len(), 1);
+
+        let parsed = &model.meshes[0].mesh;
+        let corners = |mesh: &Mesh| {
+            mesh.triangle_indices()
+                .into_iter()
+                .map(|idx| mesh.vertices[idx as usize])
+                .collect::<Vec<_>>()
+        };
+
+        let (expected, actual) = (corners(mesh), corners(parsed));
+        assert_eq!(expected.len(), actual.len());
+
+        for (expected, actual) in expected.iter().zip(&actual) {
+            assert_eq!(expected.position, actual.position);
+            // the importer normalizes normals, which moves them by a rounding error.
+            let normal = Vec3::from(expected.normal) - Vec3::from(actual.normal);
+            assert!(normal.length() < 1e-5, "{expected:?} != {actual:?}");
+        }
+    }
+
+    #[test]
+    fn obj_round_trips() {
+        round_trip_obj(&Mesh::cube(1.0));
+        round_trip_obj(&Mesh::uv_sphere(0.75, 12, 6));
+        round_trip_obj(&Mesh::torus(1.0, 0.25, 8, 6));
+    }
+
+    #[test]
+    fn obj_round_trips_without_indices() {
+        let vertices = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+            .map(|position| {
+                Vertex::builder()
+                    .position(position)
+                    .normal([0.0, 0.0, 1.0])
+                    .build()
+            })
+            .to_vec();
+
+        round_trip_obj(&Mesh::builder().vertices(vertices).build());
+    }
+
+    #[test]
+    fn stl_rejects_out_of_bounds_indices() {
+        let mut mesh = Mesh::quad(1.0, 1.0);
+        mesh.indices = Some(vec![0, 1, 4]);
+
+        let err = mesh.write_stl(Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn stl_has_a_facet_per_triangle() {
+        let mut stl = Vec::new();
+        Mesh::cube(1.0).write_stl(&mut stl).unwrap();
+
+        assert_eq!(u32::from_le_bytes(stl[80..84].try_into().unwrap()), 12);
+        assert_eq!(stl.len(), 84 + 12 * 50);
+    }
+}
@@ -1,14 +1,33 @@
-use std::time::{Duration, Instant};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 
-use wgpu::{Backends, InstanceDescriptor};
+use wgpu::InstanceDescriptor;
 use winit::{
-    dpi::PhysicalSize,
-    event::{DeviceEvent, DeviceId, Event, WindowEvent},
-    event_loop::ControlFlow,
-    window::Window,
+    dpi::{LogicalSize, PhysicalSize},
+    event::{
+        DeviceEvent, DeviceId, ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent,
+    },
+    event_loop::{ControlFlow, EventLoopWindowTarget},
+    platform::run_return::EventLoopExtRunReturn,
+    window::{Window, WindowBuilder},
 };
 
-use super::time::Time;
+use super::{
+    capture::{self, RgbaImage},
+    config::EngineConfig,
+    context::Context,
+    error::EngineError,
+    input::ActionMap,
+    pacing::FramePacer,
+    profiler::GpuProfiler,
+    replay::{RecordedEvent, Recorder, Recording, Replay},
+    stats::{FrameStats, Phase},
+    target::RenderTargets,
+    time::{TickRate, Time},
+    window::{WindowHandle, WindowId, WindowOptions},
+};
 
 pub type EventLoop = winit::event_loop::EventLoop<()>;
 pub struct WgpuConstruct(
@@ -22,187 +41,812 @@ pub struct WgpuConstruct(
     wgpu::Queue,
 );
 
+#[derive(Debug, Clone, Copy)]
 pub enum FramerateLimit {
     Unlimited,
     Limited(usize),
 }
 
+impl FramerateLimit {
+    /// The time a frame is given under the limit, a limit of 0 counts as 1 fps.
+    pub fn frame_time(self) -> Option<Duration> {
+        match self {
+            FramerateLimit::Unlimited => None,
+            FramerateLimit::Limited(fps) => Some(Duration::from_secs_f64(1.0 / fps.max(1) as f64)),
+        }
+    }
+}
+
 pub trait Framework: 'static + Sized {
     fn init(
         config: &wgpu::SurfaceConfiguration,
+        targets: &RenderTargets,
         adapter: &wgpu::Adapter,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-    ) -> Self;
+    ) -> anyhow::Result<Self>;
 
     fn resize(
         &mut self,
         _config: &wgpu::SurfaceConfiguration,
+        _targets: &RenderTargets,
         _device: &wgpu::Device,
         _queue: &wgpu::Queue,
     ) {
     }
 
+    /// Runs at a fixed rate set by [`Framework::tick_rate`], before rendering. Simulation belongs
+    /// here rather than in [`Framework::render`], so it behaves the same at any framerate.
+    fn update(&mut self, _ctx: &mut Context, _fixed_dt: Duration) {}
+
+    fn tick_rate(&self) -> TickRate {
+        TickRate::default()
+    }
+
     fn render(
         &mut self,
-        time: &Time,
+        ctx: &mut Context,
         encoder: &mut wgpu::CommandEncoder,
         view: &wgpu::TextureView,
+        targets: &RenderTargets,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
     );
 
+    /// The actions and axes available through [`Context::input`], queried once after
+    /// [`Framework::init`]. Loading them with [`ActionMap::load_or`] lets players rebind them.
+    fn action_map(&self) -> ActionMap {
+        ActionMap::default()
+    }
+
+    /// Renders the window `id`, into `view` and the window's own `targets`. By default only
+    /// [`WindowId::MAIN`] is rendered, with [`Framework::render`].
+    #[allow(clippy::too_many_arguments)]
+    fn render_window(
+        &mut self,
+        ctx: &mut Context,
+        id: WindowId,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        targets: &RenderTargets,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        if id == WindowId::MAIN {
+            self.render(ctx, encoder, view, targets, device, queue);
+        }
+    }
+
+    /// Called when the window `id` is resized, and once right after it's opened. By default only
+    /// [`WindowId::MAIN`] is passed on to [`Framework::resize`].
+    fn resize_window(
+        &mut self,
+        id: WindowId,
+        config: &wgpu::SurfaceConfiguration,
+        targets: &RenderTargets,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) {
+        if id == WindowId::MAIN {
+            self.resize(config, targets, device, queue);
+        }
+    }
+
+    /// Called after a window opened with [`Context::open_window`] was closed, by its close button
+    /// or [`Context::close_window`].
+    fn window_closed(&mut self, _ctx: &mut Context, _id: WindowId) {}
+
     fn maximum_framerate(&self) -> FramerateLimit {
         FramerateLimit::Unlimited
     }
 
+    /// Used instead of [`Framework::maximum_framerate`] while no window has focus, or every
+    /// window is occluded. Defaults to the maximum framerate.
+    fn background_framerate(&self) -> FramerateLimit {
+        self.maximum_framerate()
+    }
+
+    /// Polled about once a second, returning a title replaces the window title with it.
+    /// [`FrameStats::summary`] is handy for showing the framerate.
+    fn window_title(&self, _stats: &FrameStats) -> Option<String> {
+        None
+    }
+
+    /// A key that captures the next frame when pressed, see [`Framework::on_capture`].
+    fn screenshot_key(&self) -> Option<VirtualKeyCode> {
+        None
+    }
+
+    /// Polled before every frame, returning `true` captures the frame that is about to be rendered.
+    fn wants_capture(&mut self) -> bool {
+        false
+    }
+
+    /// Called with a captured frame. By default this writes it to `screenshot-<unix time>.png`.
+    fn on_capture(&mut self, image: RgbaImage) {
+        save_screenshot(image);
+    }
+
+    /// Called when the window's close button is pressed, returning `false` keeps the window open.
+    fn close_requested(&mut self, _ctx: &mut Context) -> bool {
+        true
+    }
+
+    /// Called once when the event loop exits, while the device is still alive. The framework is
+    /// dropped right after, before the device is.
+    fn shutdown(&mut self, _ctx: &mut Context) {}
+
+    /// Called when the window gains or loses focus. With
+    /// [`EngineConfig::pause_when_unfocused`], the framework isn't updated or rendered while unfocused.
+    fn focus_changed(&mut self, _ctx: &mut Context, _focused: bool) {}
+
+    /// Called when the application is suspended, e.g. when it's moved to the background on mobile.
+    /// Nothing is updated or rendered until [`Framework::resumed`].
+    fn suspended(&mut self, _ctx: &mut Context) {}
+
+    /// Called when the application is resumed, which some platforms also do once at startup.
+    fn resumed(&mut self, _ctx: &mut Context) {}
+
+    /// Events replayed from a [`Recording`] all come from
+    /// [`replay_device_id`](super::replay::replay_device_id).
     fn input(&mut self, _device_id: DeviceId, _event: DeviceEvent) {}
 
+    /// Like [`Framework::input`], replayed events come from
+    /// [`replay_device_id`](super::replay::replay_device_id).
     fn on_event(&mut self, _event: WindowEvent, _control_flow: &mut ControlFlow) {}
 }
 
+/// Writes `image` to `screenshot-<unix time>.png`, the default of [`Framework::on_capture`].
+pub(crate) fn save_screenshot(image: RgbaImage) {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    if let Err(err) = image.save_png(format!("screenshot-{secs}.png")) {
+        log::error!("unable to save screenshot: {err:?}");
+    }
+}
+
+/// Runs every tick of [`Framework::update`] that is due this frame, or exactly `ticks` of them
+/// when replaying a recording. Returns how many ran.
+pub(crate) fn run_fixed_updates<T: Framework>(
+    framework: &mut T,
+    ctx: &mut Context,
+    ticks: Option<u32>,
+) -> u32 {
+    let rate = framework.tick_rate();
+
+    if !rate.is_valid() {
+        log::error!(
+            "{}hz isn't a valid tick rate, skipping fixed updates",
+            rate.hz
+        );
+        return 0;
+    }
+
+    let due = ctx.time.accumulate(&rate);
+    let ticks = ticks.unwrap_or(due);
+
+    for _ in 0..ticks {
+        framework.update(ctx, rate.step());
+    }
+
+    ticks
+}
+
 // #[autodefault::autodefault]
-pub async fn init_wgpu<T: Framework>(window: Window, event_loop: EventLoop) -> WgpuConstruct {
-    env_logger::init();
+pub async fn init_wgpu<T: Framework>(
+    window: Window,
+    event_loop: EventLoop,
+    engine_config: &EngineConfig,
+) -> Result<WgpuConstruct, EngineError> {
+    let _ = env_logger::try_init();
 
     let instance = wgpu::Instance::new(InstanceDescriptor {
-        backends: Backends::all(),
+        backends: engine_config.backends(),
         ..Default::default()
-    }); // this will automatically set the Backends to "all", and use the default 'Dx12Compiler'.
+    }); // this will use the default 'Dx12Compiler'.
     let size = window.inner_size();
 
-    let surface =
-        unsafe { instance.create_surface(&window) }.expect("Unable to instantiate surface!");
+    let surface = unsafe { instance.create_surface(&window) }?;
 
     let adapter = instance
         .request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::HighPerformance,
+            power_preference: engine_config.power_preference(),
             compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
+            force_fallback_adapter: engine_config.force_fallback_adapter,
         })
         .await
-        .unwrap();
+        .ok_or(EngineError::NoAdapter)?;
+
+    let (device, queue) = request_device(&adapter, engine_config).await?;
+
+    Ok(WgpuConstruct(
+        window, event_loop, instance, size, surface, adapter, device, queue,
+    ))
+}
 
-    let (device, queue) = adapter
+pub(crate) async fn request_device(
+    adapter: &wgpu::Adapter,
+    engine_config: &EngineConfig,
+) -> Result<(wgpu::Device, wgpu::Queue), EngineError> {
+    let device = adapter
         .request_device(
             &wgpu::DeviceDescriptor {
-                features: wgpu::Features::DEPTH_CLIP_CONTROL,
-                limits: wgpu::Limits::default(),
+                features: engine_config.features(adapter)?,
+                limits: engine_config.limits(adapter),
                 label: None,
             },
             None,
         )
-        .await
-        .unwrap();
+        .await?;
 
-    WgpuConstruct(
-        window, event_loop, instance, size, surface, adapter, device, queue,
-    )
+    Ok(device)
 }
 
-pub fn run<T: Framework>(window: Window, event_loop: EventLoop) {
-    let WgpuConstruct(window, event_loop, instance, size, surface, adapter, device, queue) =
-        pollster::block_on(init_wgpu::<T>(window, event_loop));
-
-    let capibilities = surface.get_capabilities(&adapter);
-    let surface_format = capibilities
-        .formats
-        .iter()
-        .copied()
-        .filter(|f| f.describe().srgb)
-        .next()
-        .unwrap_or(capibilities.formats[0]);
-
-    let mut config = wgpu::SurfaceConfiguration {
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-        format: surface_format,
-        width: size.width,
-        height: size.height,
-        present_mode: capibilities.present_modes[0],
-        alpha_mode: capibilities.alpha_modes[0],
-        view_formats: vec![],
-    };
-
-    surface.configure(&device, &config);
+/// Opens a window as described by `engine_config`, and runs `T` in it until the event loop exits.
+///
+/// Returns once the window is closed or [`WindowHandle::request_exit`] is called, after
+/// [`Framework::shutdown`] and tearing everything down. Errors during rendering are either
+/// recovered from, or exit the event loop and are returned.
+pub fn run<T: Framework>(engine_config: EngineConfig) -> Result<(), EngineError> {
+    run_with(
+        engine_config,
+        |config, targets, adapter, device, queue, _| {
+            T::init(config, targets, adapter, device, queue)
+        },
+    )
+}
 
-    let mut framework = T::init(&config, &adapter, &device, &queue);
-    let mut time = Time::new();
+/// Like [`run`], but creates the framework with `init` instead of [`Framework::init`]. The context
+/// passed to `init` is the one the framework runs with.
+pub(crate) fn run_with<T: Framework>(
+    engine_config: EngineConfig,
+    init: impl FnOnce(
+        &wgpu::SurfaceConfiguration,
+        &RenderTargets,
+        &wgpu::Adapter,
+        &wgpu::Device,
+        &wgpu::Queue,
+        &mut Context,
+    ) -> anyhow::Result<T>,
+) -> Result<(), EngineError> {
+    let event_loop = EventLoop::new();
+    let window = WindowBuilder::new()
+        .with_title(&engine_config.title)
+        .with_inner_size(LogicalSize::new(engine_config.width, engine_config.height))
+        .build(&event_loop)?;
+
+    let WgpuConstruct(window, mut event_loop, instance, size, surface, adapter, device, queue) =
+        pollster::block_on(init_wgpu::<T>(window, event_loop, &engine_config))?;
+
+    let config = configure_surface(&engine_config, &surface, &adapter, &device, size, None)?;
+    let targets = RenderTargets::new(
+        &adapter,
+        &device,
+        &config,
+        engine_config.sample_count,
+        engine_config.depth_format(),
+    )?;
+
+    let mut ctx = Context::new(Time::new());
+    ctx.configure(&engine_config);
+    ctx.window = WindowHandle::new(window, engine_config.title.clone());
+
+    let mut framework =
+        init(&config, &targets, &adapter, &device, &queue, &mut ctx).map_err(EngineError::Init)?;
+    ctx.input.set_actions(framework.action_map());
+
+    let tick_rate = framework.tick_rate();
+
+    if !tick_rate.is_valid() {
+        return Err(EngineError::InvalidTickRate(tick_rate.hz));
+    }
 
-    let mut fps_counter = 0;
-    let mut fps_counter_time = Instant::now();
+    if engine_config.gpu_profiling {
+        ctx.profiler = GpuProfiler::new(&device, &queue);
+    }
 
-    event_loop.run(move |event, _, control_flow| {
-        fps_counter += 1;
+    let main_format = config.format;
+    let mut surfaces = HashMap::from([(
+        WindowId::MAIN,
+        WindowSurface {
+            surface,
+            config,
+            targets,
+        },
+    )]);
+
+    let mut capture_requested = false;
+    let mut suspended = false;
+    let mut was_paused = false;
+    let mut frame_started = false;
+    let mut pacer = FramePacer::default();
+
+    let tick_hz = tick_rate.hz;
+    let mut recorder = engine_config.record.as_ref().and_then(|path| {
+        Recorder::create(path, tick_hz)
+            .map_err(|err| log::error!("unable to record input: {err}"))
+            .ok()
+    });
+    let mut replay = engine_config.replay.as_ref().and_then(|path| {
+        Recording::load(path)
+            .map(|recording| Replay::new(recording, tick_hz))
+            .map_err(|err| log::error!("unable to replay {path:?}: {err}"))
+            .ok()
+    });
+    let mut fatal_error = None;
 
-        if fps_counter_time.elapsed().as_secs() >= 1 {
-            let fps = fps_counter as f64 / fps_counter_time.elapsed().as_secs_f64();
+    let mut last_title = Instant::now();
+    let mut last_stats_log = Instant::now();
 
-            window.set_title(&format!("Engine [{:.2} fps] (todo)", fps));
+    event_loop.run_return(|event, target, control_flow| {
+        let paused = suspended
+            || ctx.windows().all(|(_, window)| window.is_minimized())
+            || (engine_config.pause_when_unfocused
+                && !ctx.windows().any(|(_, window)| window.is_focused()));
 
-            fps_counter = 0;
-            fps_counter_time = Instant::now();
+        if was_paused && !paused {
+            // the time spent paused shouldn't count as one very long frame.
+            ctx.time.resume();
+            pacer.reset();
         }
 
-        *control_flow = ControlFlow::Poll;
+        was_paused = paused;
 
-        let _ = (&instance, &adapter);
+        // exiting is sticky, so anything setting the control flow later on is ignored.
+        *control_flow = match (ctx.window.exit_requested(), paused) {
+            (true, _) => ControlFlow::Exit,
+            // nothing is rendered while paused, so there's no reason to wake up for anything but events.
+            (false, true) => ControlFlow::Wait,
+            (false, false) => pacer.control_flow(),
+        };
 
         match event {
-            Event::WindowEvent { event, .. } => {
+            Event::WindowEvent { window_id, event } => {
+                let Some(id) = ctx.window_id(window_id) else {
+                    return;
+                };
+
+                let start = Instant::now();
+                let recorded = RecordedEvent::from_window_event(&event);
+                // real input is ignored while a recording replays.
+                let ignored =
+                    replay.is_some() && matches!(recorded, Some(event) if event.is_input());
+
+                if let (Some(recorder), Some(recorded)) = (&mut recorder, recorded) {
+                    recorder.record(id, recorded);
+                }
+
+                if !ignored {
+                    ctx.input.handle_window_event(&event);
+                }
+
                 match event {
                     WindowEvent::Resized(size)
                     | WindowEvent::ScaleFactorChanged {
                         new_inner_size: &mut size,
                         ..
                     } => {
-                        config.width = size.width.max(1);
-                        config.height = size.height.max(1);
-
-                        framework.resize(&config, &device, &queue);
-                        surface.configure(&device, &config);
+                        // minimized windows have no size, and no surface can be configured for them.
+                        let minimized = size.width == 0 || size.height == 0;
+
+                        if let Some(window) = ctx.window_by_id_mut(id) {
+                            window.set_minimized(minimized);
+                        }
+
+                        if let (false, Some(window_surface)) = (minimized, surfaces.get_mut(&id)) {
+                            window_surface.resize(&device, size);
+                            framework.resize_window(
+                                id,
+                                &window_surface.config,
+                                &window_surface.targets,
+                                &device,
+                                &queue,
+                            );
+                        }
+                    }
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(key),
+                                ..
+                            },
+                        ..
+                    } if Some(key) == framework.screenshot_key() => {
+                        capture_requested = true;
+                    }
+                    WindowEvent::CloseRequested if id != WindowId::MAIN => ctx.close_window(id),
+                    WindowEvent::CloseRequested if framework.close_requested(&mut ctx) => {
+                        ctx.window.request_exit();
+                        *control_flow = ControlFlow::Exit;
+                    }
+                    WindowEvent::Focused(focused) => {
+                        if let Some(window) = ctx.window_by_id_mut(id) {
+                            window.set_focused(focused);
+                        }
+
+                        if id == WindowId::MAIN {
+                            framework.focus_changed(&mut ctx, focused);
+                        }
+                    }
+                    WindowEvent::Occluded(occluded) => {
+                        if let Some(window) = ctx.window_by_id_mut(id) {
+                            window.set_occluded(occluded);
+                        }
                     }
                     _ => (),
                 };
-                framework.on_event(event, control_flow);
+
+                if !ignored {
+                    framework.on_event(event, control_flow);
+                }
+
+                ctx.stats.record_phase(Phase::Events, start.elapsed());
+            }
+            Event::DeviceEvent { device_id, event } => {
+                let start = Instant::now();
+                let recorded = RecordedEvent::from_device_event(&event);
+
+                if let (Some(recorder), Some(recorded)) = (&mut recorder, recorded) {
+                    recorder.record(WindowId::MAIN, recorded);
+                }
+
+                if replay.is_some() && recorded.is_some() {
+                    return;
+                }
+
+                ctx.input.handle_device_event(&event);
+                framework.input(device_id, event);
+                ctx.stats.record_phase(Phase::Events, start.elapsed());
+            }
+            Event::Suspended => {
+                suspended = true;
+                framework.suspended(&mut ctx);
+            }
+            Event::Resumed => {
+                suspended = false;
+                framework.resumed(&mut ctx);
+            }
+            Event::MainEventsCleared => {
+                // opening a window needs the event loop, so windows are opened and closed in between frames.
+                for id in std::mem::take(&mut ctx.closing) {
+                    surfaces.remove(&id);
+
+                    if ctx.windows.remove(&id).is_some() {
+                        framework.window_closed(&mut ctx, id);
+                    }
+                }
+
+                for (id, options) in std::mem::take(&mut ctx.opening) {
+                    let opened = open_window(
+                        target,
+                        &instance,
+                        &adapter,
+                        &device,
+                        &engine_config,
+                        main_format,
+                        &options,
+                    );
+
+                    match opened {
+                        Ok((window, window_surface)) => {
+                            framework.resize_window(
+                                id,
+                                &window_surface.config,
+                                &window_surface.targets,
+                                &device,
+                                &queue,
+                            );
+
+                            surfaces.insert(id, window_surface);
+                            ctx.windows
+                                .insert(id, WindowHandle::new(window, options.title));
+                        }
+                        Err(err) => log::error!("unable to open window {:?}: {err}", options.title),
+                    }
+                }
+
+                let background = !ctx.windows().any(|(_, window)| window.is_focused())
+                    || ctx.windows().all(|(_, window)| window.is_occluded());
+                let frame_time = match background {
+                    true => framework.background_framerate().frame_time(),
+                    false => framework.maximum_framerate().frame_time(),
+                };
+
+                if paused || !pacer.wait(frame_time) {
+                    return;
+                }
+
+                if let Some(jitter) = pacer.begin_frame(frame_time) {
+                    ctx.stats.record_jitter(jitter);
+                }
+
+                let start = Instant::now();
+
+                let replayed = replay
+                    .as_mut()
+                    .map(|replay| replay.next_frame(&mut framework, &mut ctx, control_flow));
+
+                let ticks = match replayed {
+                    Some(None) => {
+                        log::info!("the recording is over, taking input again");
+                        replay = None;
+                        ctx.time.set_fixed_delta(None);
+                        None
+                    }
+                    Some(ticks) => ticks,
+                    None => None,
+                };
+
+                ctx.stats.record_phase(Phase::Events, start.elapsed());
+
+                let start = Instant::now();
+                let ticks = run_fixed_updates(&mut framework, &mut ctx, ticks);
+                ctx.stats.record_phase(Phase::Update, start.elapsed());
+
+                let start = Instant::now();
+                ctx.uploads.process(&device, &queue);
+                ctx.stats.record_phase(Phase::Upload, start.elapsed());
+
+                if let Some(recorder) = &mut recorder {
+                    recorder.frame(ticks);
+                }
+
+                frame_started = true;
+
+                for (_, window) in ctx.windows().filter(|(_, window)| !window.is_minimized()) {
+                    window.request_redraw();
+                }
             }
-            Event::DeviceEvent { device_id, event } => framework.input(device_id, event),
-            Event::RedrawRequested(_) => {
+            Event::RedrawRequested(window_id) => {
+                let Some(id) = ctx.window_id(window_id).filter(|_| frame_started) else {
+                    return;
+                };
+
+                let (Some(window_surface), Some(false)) = (
+                    surfaces.get_mut(&id),
+                    ctx.window_by_id(id).map(WindowHandle::is_minimized),
+                ) else {
+                    return;
+                };
+
+                let WindowSurface {
+                    surface,
+                    config,
+                    targets,
+                } = window_surface;
+
                 let frame = match surface.get_current_texture() {
                     Ok(frame) => frame,
-                    Err(_) => {
-                        surface.configure(&device, &config);
-                        surface
-                            .get_current_texture()
-                            .expect("Failed to acquire next surface texture!")
+                    Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                        // the surface no longer matches the window, reconfigure it and try again next frame.
+                        surface.configure(&device, config);
+                        return;
+                    }
+                    Err(wgpu::SurfaceError::Timeout) => {
+                        log::warn!("timed out acquiring surface texture, skipping frame");
+                        return;
+                    }
+                    Err(wgpu::SurfaceError::OutOfMemory) => {
+                        fatal_error = Some(EngineError::OutOfMemory);
+                        *control_flow = ControlFlow::Exit;
+                        return;
                     }
                 };
-                let view = frame
-                    .texture
-                    .create_view(&wgpu::TextureViewDescriptor::default());
+                let capture = id == WindowId::MAIN
+                    && std::mem::take(&mut capture_requested) | framework.wants_capture();
+                // surfaces that can't be copied from are rendered offscreen for the captured
+                // frame instead, which then isn't presented.
+                let offscreen = (capture && !config.usage.contains(wgpu::TextureUsages::COPY_SRC))
+                    .then(|| capture::create_capture_texture(&device, config));
+                let texture = offscreen.as_ref().unwrap_or(&frame.texture);
+                let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
 
                 let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
                     label: Some("Render Encoder"),
                 });
 
-                framework.render(&time, &mut encoder, &view, &device, &queue);
+                let start = Instant::now();
+                framework.render_window(
+                    &mut ctx,
+                    id,
+                    &mut encoder,
+                    &view,
+                    targets,
+                    &device,
+                    &queue,
+                );
+                ctx.stats.record_phase(Phase::Render, start.elapsed());
+
+                let start = Instant::now();
+                ctx.profiler.resolve(&device, &mut encoder);
+                let pending = capture.then(|| {
+                    capture::copy_texture(
+                        &device,
+                        &mut encoder,
+                        texture,
+                        config.format,
+                        config.width,
+                        config.height,
+                    )
+                });
                 queue.submit(std::iter::once(encoder.finish()));
+                ctx.profiler.end_frame(&device);
+                ctx.stats.record_phase(Phase::Submit, start.elapsed());
+
+                if let Some(pending) = pending {
+                    match pending.and_then(|pending| pending.read(&device)) {
+                        Ok(image) => framework.on_capture(image),
+                        Err(err) => log::error!("unable to capture frame: {err:?}"),
+                    }
+                }
+
+                if offscreen.is_some() {
+                    return;
+                }
 
+                let start = Instant::now();
                 frame.present();
-                time.post_update();
+                ctx.stats.record_phase(Phase::Present, start.elapsed());
             }
             Event::RedrawEventsCleared => {
-                if let FramerateLimit::Limited(fps) = framework.maximum_framerate() {
-                    let target_frametime = Duration::from_secs_f64(1.0 / (fps as f64));
-                    let now = Instant::now();
-                    let delta = time.time_delta();
+                // a frame lasts from updating to every window having been rendered.
+                if !std::mem::take(&mut frame_started) {
+                    return;
+                }
+
+                ctx.end_frame();
+
+                if last_title.elapsed() >= Duration::from_secs(1) {
+                    if let Some(title) = framework.window_title(&ctx.stats) {
+                        ctx.window.set_title(title);
+                    }
+
+                    last_title = Instant::now();
+                }
+
+                if let Some(interval) = engine_config.stats_log_interval {
+                    if last_stats_log.elapsed().as_secs_f64() >= interval {
+                        log::info!("{}", ctx.stats.summary());
+
+                        for scope in ctx.profiler.results() {
+                            log::info!(
+                                "  {}: {:.3}ms",
+                                scope.name,
+                                scope.duration.as_secs_f64() * 1000.0
+                            );
+                        }
 
-                    *control_flow = ControlFlow::WaitUntil(now + target_frametime - delta);
+                        last_stats_log = Instant::now();
+                    }
                 }
             }
-            Event::MainEventsCleared => {
-                window.request_redraw();
+            Event::LoopDestroyed => {
+                framework.shutdown(&mut ctx);
+                device.poll(wgpu::Maintain::Wait);
             }
             _ => (),
         }
     });
+
+    // tear everything down in order, the framework's resources before the device and every
+    // surface before its window. the context holds the windows, and the profiler's GPU resources.
+    drop(framework);
+    drop(surfaces);
+    drop(ctx);
+    drop(queue);
+    drop(device);
+
+    match fatal_error {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// The surface of a window, and the targets rendered into it.
+struct WindowSurface {
+    surface: wgpu::Surface,
+    config: wgpu::SurfaceConfiguration,
+    targets: RenderTargets,
+}
+
+impl WindowSurface {
+    fn resize(&mut self, device: &wgpu::Device, size: PhysicalSize<u32>) {
+        self.config.width = size.width;
+        self.config.height = size.height;
+
+        self.targets.resize(device, &self.config);
+        self.surface.configure(device, &self.config);
+    }
+}
+
+/// Configures `surface` as described by `engine_config`, preferring `format` over the configured
+/// surface format so every window can share pipelines.
+fn configure_surface(
+    engine_config: &EngineConfig,
+    surface: &wgpu::Surface,
+    adapter: &wgpu::Adapter,
+    device: &wgpu::Device,
+    size: PhysicalSize<u32>,
+    format: Option<wgpu::TextureFormat>,
+) -> Result<wgpu::SurfaceConfiguration, EngineError> {
+    let capabilities = surface.get_capabilities(adapter);
+
+    let format = format
+        .filter(|format| capabilities.formats.contains(format))
+        .or_else(|| engine_config.surface_format(&capabilities.formats));
+
+    let (Some(format), Some(present_mode), Some(alpha_mode)) = (
+        format,
+        engine_config.present_mode(&capabilities.present_modes),
+        engine_config.alpha_mode(&capabilities.alpha_modes),
+    ) else {
+        return Err(EngineError::UnsupportedSurface);
+    };
+
+    // wgpu doesn't say which usages a surface supports, and only these backends' surfaces can
+    // be copied from so frames can be captured as they're presented.
+    let usage = match adapter.get_info().backend {
+        wgpu::Backend::Vulkan | wgpu::Backend::Dx12 => {
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC
+        }
+        _ => wgpu::TextureUsages::RENDER_ATTACHMENT,
+    };
+
+    let config = wgpu::SurfaceConfiguration {
+        usage,
+        format,
+        width: size.width.max(1),
+        height: size.height.max(1),
+        present_mode,
+        alpha_mode,
+        view_formats: vec![],
+    };
+
+    surface.configure(device, &config);
+    Ok(config)
+}
+
+fn open_window(
+    target: &EventLoopWindowTarget<()>,
+    instance: &wgpu::Instance,
+    adapter: &wgpu::Adapter,
+    device: &wgpu::Device,
+    engine_config: &EngineConfig,
+    format: wgpu::TextureFormat,
+    options: &WindowOptions,
+) -> Result<(Window, WindowSurface), EngineError> {
+    let window = WindowBuilder::new()
+        .with_title(&options.title)
+        .with_inner_size(LogicalSize::new(options.width, options.height))
+        .build(target)?;
+
+    let surface = unsafe { instance.create_surface(&window) }?;
+    let config = configure_surface(
+        engine_config,
+        &surface,
+        adapter,
+        device,
+        window.inner_size(),
+        Some(format),
+    )?;
+    let targets = RenderTargets::new(
+        adapter,
+        device,
+        &config,
+        engine_config.sample_count,
+        engine_config.depth_format(),
+    )?;
+
+    Ok((
+        window,
+        WindowSurface {
+            surface,
+            config,
+            targets,
+        },
+    ))
 }
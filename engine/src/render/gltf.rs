@@ -0,0 +1,304 @@
+use std::path::Path;
+
+use anyhow::{bail, Context as _};
+use glam::Mat4;
+
+use super::{
+    bundle::mesh::{Bundles, MeshBundle},
+    color::Color,
+    handle::HandleId,
+    material::color::StaticColorMaterial,
+    mesh::Mesh,
+    vertex::{Transform, Vertex},
+};
+
+/// A primitive of a glTF mesh, and every node it's placed at.
+#[derive(Debug)]
+pub struct GltfMesh {
+    /// The name of the glTF mesh the primitive belongs to.
+    pub name: Option<String>,
+    pub mesh: Mesh,
+    /// The base color factor of the primitive's material, textures aren't supported.
+    pub color: Color,
+    pub instances: Vec<Transform>,
+}
+
+/// The meshes of a glTF 2.0 scene, placed where the node hierarchy puts them.
+///
+/// Only triangle primitives are imported, and of their materials only the base color factor.
+/// Anything else is skipped with a warning, as are the extensions the file uses. Nodes are placed
+/// with a [`Transform`], so shear is lost with a warning, and primitives placed by mirroring nodes
+/// get a copy with their triangles wound the other way.
+#[derive(Debug, Default)]
+pub struct GltfScene {
+    pub meshes: Vec<GltfMesh>,
+    /// Extensions the file uses but the importer ignores.
+    pub unsupported_extensions: Vec<String>,
+}
+
+impl GltfScene {
+    /// Loads a `.gltf` or `.glb` file, with its buffers whether they're embedded, base64 encoded
+    /// or next to it.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let ::gltf::Gltf { document, blob } =
+            ::gltf::Gltf::open(path).with_context(|| format!("unable to open {path:?}"))?;
+
+        let buffers = ::gltf::import_buffers(&document, path.parent(), blob)
+            .with_context(|| format!("unable to load the buffers of {path:?}"))?;
+
+        Self::from_document(&document, &buffers)
+            .with_context(|| format!("unable to import {path:?}"))
+    }
+
+    /// Imports a `.gltf` or `.glb` file from memory. Buffers have to be embedded or base64
+    /// encoded, as there's nowhere to look for external ones.
+    pub fn from_slice(bytes: &[u8]) -> anyhow::Result<Self> {
+        let ::gltf::Gltf { document, blob } = ::gltf::Gltf::from_slice(bytes)?;
+        let buffers = ::gltf::import_buffers(&document, None, blob)?;
+
+        Self::from_document(&document, &buffers)
+    }
+
+    fn from_document(
+        document: &::gltf::Document,
+        buffers: &[::gltf::buffer::Data],
+    ) -> anyhow::Result<Self> {
+        let required = document.extensions_required().collect::<Vec<_>>();
+
+        if !required.is_empty() {
+            bail!("the file requires unsupported extensions {required:?}");
+        }
+
+        let unsupported_extensions = document
+            .extensions_used()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+
+        for extension in &unsupported_extensions {
+            log::warn!("ignoring the unsupported glTF extension {extension}");
+        }
+
+        // where every mesh is placed, by mesh index.
+        let mut placements = vec![Vec::new(); document.meshes().len()];
+
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next());
+
+        let roots = match scene {
+            Some(scene) => scene.nodes().collect::<Vec<_>>(),
+            None => Vec::new(),
+        };
+
+        let mut nodes = roots
+            .into_iter()
+            .map(|node| (node, Mat4::IDENTITY))
+            .collect::<Vec<_>>();
+
+        while let Some((node, parent)) = nodes.pop() {
+            let world = parent * Mat4::from_cols_array_2d(&node.transform().matrix());
+
+            if let Some(mesh) = node.mesh() {
+                placements[mesh.index()].push(world);
+            }
+
+            nodes.extend(node.children().map(|child| (child, world)));
+        }
+
+        let mut meshes = Vec::new();
+
+        for mesh in document.meshes() {
+            // mirroring nodes turn the triangles inside out, so they get a copy wound the other way.
+            let (mirrored, kept): (Vec<_>, Vec<_>) = placements[mesh.index()]
+                .iter()
+                .map(|world| (world.determinant() < 0.0, to_transform(world, mesh.name())))
+                .partition(|(mirrored, _)| *mirrored);
+
+            let [kept, mirrored] = [kept, mirrored].map(|placed| {
+                placed
+                    .into_iter()
+                    .map(|(_, transform)| transform)
+                    .collect::<Vec<_>>()
+            });
+
+            if kept.is_empty() && mirrored.is_empty() {
+                continue;
+            }
+
+            for primitive in mesh.primitives() {
+                let name = mesh.name().map(str::to_string);
+
+                if primitive.mode() != ::gltf::mesh::Mode::Triangles {
+                    log::warn!(
+                        "skipping primitive {} of mesh {name:?}, {:?} primitives aren't supported",
+                        primitive.index(),
+                        primitive.mode()
+                    );
+                    continue;
+                }
+
+                let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+                let Some(positions) = reader.read_positions() else {
+                    log::warn!(
+                        "skipping primitive {} of mesh {name:?}, it has no positions",
+                        primitive.index()
+                    );
+                    continue;
+                };
+
+                let positions = positions.collect::<Vec<_>>();
+                let normals = reader
+                    .read_normals()
+                    .map(|normals| normals.collect::<Vec<_>>());
+
+                let vertices = positions
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, position)| {
+                        let normal = normals
+                            .as_ref()
+                            .and_then(|normals| normals.get(idx))
+                            .copied()
+                            .unwrap_or_default();
+
+                        Vertex::builder().position(*position).normal(normal).build()
+                    })
+                    .collect::<Vec<_>>();
+
+                let indices = match reader.read_indices() {
+                    Some(indices) => indices.into_u32().collect(),
+                    None => (0..vertices.len() as u32).collect(),
+                };
+
+                let mut mesh = Mesh::builder().vertices(vertices).indices(indices).build();
+
+                if normals.is_none() {
+                    mesh.generate_normals();
+                }
+
+                let [r, g, b, a] = primitive
+                    .material()
+                    .pbr_metallic_roughness()
+                    .base_color_factor();
+
+                let color = Color::linear(r as f64, g as f64, b as f64, a as f64);
+
+                if !mirrored.is_empty() {
+                    let mut mesh = mesh.clone();
+                    mesh.flip_winding();
+
+                    meshes.push(GltfMesh {
+                        name: name.clone(),
+                        mesh,
+                        color,
+                        instances: mirrored.clone(),
+                    });
+                }
+
+                if !kept.is_empty() {
+                    meshes.push(GltfMesh {
+                        name,
+                        mesh,
+                        color,
+                        instances: kept.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            meshes,
+            unsupported_extensions,
+        })
+    }
+
+    /// Adds every mesh to `bundles`, with an instance for every node it's placed at.
+    pub fn add_to(self, bundles: &mut Bundles<StaticColorMaterial>) -> Vec<HandleId> {
+        self.meshes
+            .into_iter()
+            .map(|mesh| {
+                let material = StaticColorMaterial::builder().color(mesh.color).build();
+                let id = bundles.add(
+                    MeshBundle::builder()
+                        .mesh(mesh.mesh)
+                        .material(material)
+                        .build(),
+                );
+
+                for transform in mesh.instances {
+                    bundles.instance(id, transform);
+                }
+
+                id
+            })
+            .collect()
+    }
+}
+
+/// Decomposes a node's world matrix into a [`Transform`], which can't hold shear.
+fn to_transform(world: &Mat4, mesh: Option<&str>) -> Transform {
+    let (scale, rotation, translation) = world.to_scale_rotation_translation();
+    let recomposed = Mat4::from_scale_rotation_translation(scale, rotation, translation);
+
+    if !world.abs_diff_eq(recomposed, 1e-4 * scale.abs().max_element().max(1.0)) {
+        log::warn!("an instance of mesh {mesh:?} is sheared, which gets lost on import");
+    }
+
+    Transform::builder()
+        .translation(translation)
+        .rotation(rotation)
+        .scale(scale)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A single triangle, placed by a plain node and by a mirroring one.
+    const MIRRORED: &str = r#"{
+        "asset": { "version": "2.0" },
+        "scene": 0,
+        "scenes": [{ "nodes": [0, 1] }],
+        "nodes": [
+            { "mesh": 0 },
+            { "mesh": 0, "scale": [-1.0, 1.0, 1.0] }
+        ],
+        "meshes": [{ "name": "triangle", "primitives": [{ "attributes": { "POSITION": 0 } }] }],
+        "accessors": [{
+            "bufferView": 0,
+            "componentType": 5126,
+            "count": 3,
+            "type": "VEC3",
+            "min": [0.0, 0.0, 0.0],
+            "max": [1.0, 1.0, 0.0]
+        }],
+        "bufferViews": [{ "buffer": 0, "byteLength": 36 }],
+        "buffers": [{
+            "byteLength": 36,
+            "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAA"
+        }]
+    }"#;
+
+    #[test]
+    fn mirrored_instances_get_flipped_triangles() {
+        let scene = GltfScene::from_slice(MIRRORED.as_bytes()).unwrap();
+        assert_eq!(scene.meshes.len(), 2);
+
+        let mirrored = scene
+            .meshes
+            .iter()
+            .find(|mesh| mesh.instances[0].matrix().determinant() < 0.0)
+            .unwrap();
+        let kept = scene
+            .meshes
+            .iter()
+            .find(|mesh| mesh.instances[0].matrix().determinant() > 0.0)
+            .unwrap();
+
+        assert_eq!(kept.mesh.triangle_indices(), [0, 1, 2]);
+        assert_eq!(mirrored.mesh.triangle_indices(), [0, 2, 1]);
+    }
+}
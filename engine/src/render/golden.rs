@@ -0,0 +1,238 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::bail;
+use typed_builder::TypedBuilder;
+
+use super::{
+    capture::{self, RgbaImage},
+    config::EngineConfig,
+    context::Context,
+    framework::Framework,
+    headless::{self, HeadlessOptions, Until},
+    target::RenderTargets,
+    time::Time,
+};
+
+/// Setting this environment variable overwrites reference images with the rendered output instead of comparing them.
+pub const BLESS_ENV: &str = "ENGINE_BLESS";
+
+/// Renders offscreen for a fixed amount of frames with a deterministic clock, and compares the
+/// last frame against a reference PNG.
+///
+/// On failure, `<reference>.actual.png` and `<reference>.diff.png` are written next to the reference.
+/// A missing reference is written from the rendered output, but the check still fails so it gets reviewed.
+#[derive(TypedBuilder, Debug, Clone)]
+pub struct GoldenImage {
+    #[builder(setter(into))]
+    reference: PathBuf,
+    #[builder(default = 128)]
+    width: u32,
+    #[builder(default = 128)]
+    height: u32,
+    #[builder(default = 1)]
+    frames: usize,
+    #[builder(default = Duration::from_secs_f64(1.0 / 60.0))]
+    frame_delta: Duration,
+    #[builder(default = wgpu::TextureFormat::Rgba8UnormSrgb)]
+    format: wgpu::TextureFormat,
+    /// The largest per-channel difference that still counts as the same pixel.
+    #[builder(default = 2)]
+    tolerance: u8,
+    /// How many pixels may exceed `tolerance` before the comparison fails.
+    #[builder(default)]
+    max_differing_pixels: usize,
+}
+
+#[derive(Debug)]
+pub struct Comparison {
+    pub differing_pixels: usize,
+    pub max_difference: u8,
+    pub diff: RgbaImage,
+}
+
+impl GoldenImage {
+    /// Renders `T` and compares its last frame against the reference.
+    pub fn check_framework<T: Framework>(&self) -> anyhow::Result<()> {
+        let options = HeadlessOptions::builder()
+            .width(self.width)
+            .height(self.height)
+            .format(self.format)
+            .fixed_delta(self.frame_delta)
+            .build();
+
+        let run = headless::run_headless::<T>(options, Until::Frames(self.frames))?;
+        self.check_image(&run.capture()?)
+    }
+
+    /// Like [`GoldenImage::check_framework`], but renders with a closure that has the same
+    /// parameters as [`Framework::render`].
+    pub fn check_with(
+        &self,
+        mut render: impl FnMut(
+            &mut Context,
+            &mut wgpu::CommandEncoder,
+            &wgpu::TextureView,
+            &RenderTargets,
+            &wgpu::Device,
+            &wgpu::Queue,
+        ),
+    ) -> anyhow::Result<()> {
+        let engine_config = EngineConfig::default();
+        let (_instance, adapter, device, queue) =
+            pollster::block_on(headless::init_headless_wgpu(&engine_config))?;
+
+        let config = HeadlessOptions::builder()
+            .width(self.width)
+            .height(self.height)
+            .format(self.format)
+            .build()
+            .surface_config();
+
+        let texture = capture::create_capture_texture(&device, &config);
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let targets = RenderTargets::new(
+            &adapter,
+            &device,
+            &config,
+            engine_config.sample_count,
+            engine_config.depth_format(),
+        )?;
+        let mut ctx = Context::new(Time::fixed(self.frame_delta));
+
+        for _ in 0..self.frames {
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Golden Render Encoder"),
+            });
+
+            render(&mut ctx, &mut encoder, &view, &targets, &device, &queue);
+            queue.submit(std::iter::once(encoder.finish()));
+            ctx.end_frame();
+        }
+
+        let actual = capture::capture_texture(
+            &device,
+            &queue,
+            &texture,
+            config.format,
+            config.width,
+            config.height,
+        )?;
+
+        self.check_image(&actual)
+    }
+
+    /// Panicking version of [`GoldenImage::check_framework`], for use in `#[test]`s.
+    pub fn assert_framework<T: Framework>(&self) {
+        if let Err(err) = self.check_framework::<T>() {
+            panic!("{err:?}");
+        }
+    }
+
+    /// Compares an already rendered image against the reference.
+    pub fn check_image(&self, actual: &RgbaImage) -> anyhow::Result<()> {
+        let bless = std::env::var_os(BLESS_ENV).is_some();
+
+        if bless || !self.reference.exists() {
+            actual.save_png(&self.reference)?;
+
+            if bless {
+                return Ok(());
+            }
+
+            bail!(
+                "no reference image existed, wrote the rendered output to {:?}; check it and commit it",
+                self.reference
+            );
+        }
+
+        let expected = RgbaImage::load_png(&self.reference)?;
+
+        if (expected.width, expected.height) != (actual.width, actual.height) {
+            actual.save_png(self.sibling("actual"))?;
+
+            bail!(
+                "reference {:?} is {}x{}, but the rendered image is {}x{}",
+                self.reference,
+                expected.width,
+                expected.height,
+                actual.width,
+                actual.height
+            );
+        }
+
+        let comparison = compare(actual, &expected, self.tolerance);
+
+        if comparison.differing_pixels > self.max_differing_pixels {
+            let actual_path = self.sibling("actual");
+            let diff_path = self.sibling("diff");
+
+            actual.save_png(&actual_path)?;
+            comparison.diff.save_png(&diff_path)?;
+
+            bail!(
+                "{} pixels differ from {:?} by more than {} (max difference {}, budget {}); wrote {:?} and {:?}",
+                comparison.differing_pixels,
+                self.reference,
+                self.tolerance,
+                comparison.max_difference,
+                self.max_differing_pixels,
+                actual_path,
+                diff_path
+            );
+        }
+
+        Ok(())
+    }
+
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let stem = self
+            .reference
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy();
+
+        self.reference
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(format!("{stem}.{suffix}.png"))
+    }
+}
+
+/// Compares two images of the same size channel by channel.
+///
+/// The diff image marks differing pixels in red, scaled by how much they differ, on top of a faded
+/// copy of `expected`.
+pub fn compare(actual: &RgbaImage, expected: &RgbaImage, tolerance: u8) -> Comparison {
+    let mut diff = RgbaImage::new(expected.width, expected.height);
+    let mut differing_pixels = 0;
+    let mut max_difference = 0;
+
+    for y in 0..expected.height {
+        for x in 0..expected.width {
+            let a = actual.pixel(x, y);
+            let e = expected.pixel(x, y);
+
+            let difference = (0..4).map(|i| a[i].abs_diff(e[i])).max().unwrap_or(0);
+            max_difference = max_difference.max(difference);
+
+            let pixel = if difference > tolerance {
+                differing_pixels += 1;
+                [128 + difference / 2, 0, 0, 255]
+            } else {
+                let luma = ((e[0] as u32 + e[1] as u32 + e[2] as u32) / 3 / 4) as u8;
+                [luma, luma, luma, 255]
+            };
+
+            diff.set_pixel(x, y, pixel);
+        }
+    }
+
+    Comparison {
+        differing_pixels,
+        max_difference,
+        diff,
+    }
+}
@@ -0,0 +1,175 @@
+use wgpu::util::DeviceExt;
+
+use super::color::Color;
+
+/// The space a [`Gradient`] blends between its stops in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Interpolation {
+    /// See [`Color::lerp`].
+    Linear,
+    /// See [`Color::lerp_srgb`].
+    Srgb,
+    /// See [`Color::lerp_oklab`].
+    #[default]
+    Oklab,
+}
+
+impl Interpolation {
+    pub fn lerp(self, from: Color, to: Color, t: f64) -> Color {
+        match self {
+            Interpolation::Linear => from.lerp(to, t),
+            Interpolation::Srgb => from.lerp_srgb(to, t),
+            Interpolation::Oklab => from.lerp_oklab(to, t),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientStop {
+    pub at: f64,
+    pub color: Color,
+}
+
+/// Colors placed along `0.0..=1.0`, for things like heatmaps, skies or biome tints.
+///
+/// Stops are kept ordered by where they are. Two stops at the same place make a hard edge, with
+/// the one added last on the far side of it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Gradient {
+    stops: Vec<GradientStop>,
+    interpolation: Interpolation,
+}
+
+impl Gradient {
+    pub fn new(interpolation: Interpolation) -> Self {
+        Self {
+            stops: Vec::new(),
+            interpolation,
+        }
+    }
+
+    /// A gradient with stops spread evenly from 0 to 1.
+    pub fn even(interpolation: Interpolation, colors: impl IntoIterator<Item = Color>) -> Self {
+        let colors = colors.into_iter().collect::<Vec<_>>();
+        let last = colors.len().saturating_sub(1).max(1) as f64;
+
+        colors
+            .into_iter()
+            .enumerate()
+            .fold(Self::new(interpolation), |gradient, (idx, color)| {
+                gradient.with_stop(idx as f64 / last, color)
+            })
+    }
+
+    pub fn with_stop(mut self, at: f64, color: Color) -> Self {
+        self.add_stop(at, color);
+        self
+    }
+
+    pub fn add_stop(&mut self, at: f64, color: Color) {
+        let idx = self.stops.partition_point(|stop| stop.at <= at);
+        self.stops.insert(idx, GradientStop { at, color });
+    }
+
+    pub fn stops(&self) -> &[GradientStop] {
+        &self.stops
+    }
+
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+    }
+
+    /// The color at `t`, which is the first or last stop's color outside of the stops, and the
+    /// first stop's at NaN. A gradient without stops is transparent everywhere.
+    pub fn sample(&self, t: f64) -> Color {
+        let (Some(first), Some(last)) = (self.stops.first(), self.stops.last()) else {
+            return Color::linear(0.0, 0.0, 0.0, 0.0);
+        };
+
+        if t.is_nan() || t <= first.at {
+            return first.color;
+        }
+
+        if t >= last.at {
+            return last.color;
+        }
+
+        let idx = self.stops.partition_point(|stop| stop.at <= t);
+        let (from, to) = (self.stops[idx - 1], self.stops[idx]);
+        let span = to.at - from.at;
+
+        match span > 0.0 {
+            true => self
+                .interpolation
+                .lerp(from.color, to.color, (t - from.at) / span),
+            false => to.color,
+        }
+    }
+
+    /// Samples `width` evenly spaced colors, the first at 0 and the last at 1.
+    pub fn bake(&self, width: u32) -> Vec<Color> {
+        let last = width.saturating_sub(1).max(1) as f64;
+
+        (0..width)
+            .map(|idx| self.sample(idx as f64 / last))
+            .collect()
+    }
+
+    /// Bakes the gradient into a 1D `Rgba8UnormSrgb` texture `width` texels wide, so shaders can
+    /// sample it with a filtering sampler. Colors are clamped to `0.0..=1.0`.
+    pub fn to_texture(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        width: u32,
+        label: Option<&str>,
+    ) -> wgpu::Texture {
+        let width = width.max(1);
+        let texels = self
+            .bake(width)
+            .into_iter()
+            .flat_map(Color::to_rgba8)
+            .collect::<Vec<_>>();
+
+        device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label,
+                size: wgpu::Extent3d {
+                    width,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D1,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            },
+            &texels,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_between_and_outside_the_stops() {
+        let black = Color::linear(0.0, 0.0, 0.0, 1.0);
+        let white = Color::linear(1.0, 1.0, 1.0, 1.0);
+        let gradient = Gradient::even(Interpolation::Linear, [black, white]);
+
+        assert_eq!(gradient.sample(-1.0), black);
+        assert_eq!(gradient.sample(0.5), Color::linear(0.5, 0.5, 0.5, 1.0));
+        assert_eq!(gradient.sample(2.0), white);
+        assert_eq!(gradient.sample(f64::NAN), black);
+        assert_eq!(gradient.sample(f64::INFINITY), white);
+    }
+}
@@ -0,0 +1,873 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Write,
+};
+
+use anyhow::bail;
+use typed_builder::TypedBuilder;
+use wgpu::{
+    Buffer, BufferUsages, Color, CommandEncoder, ComputePass, Device, Operations, RenderPass,
+    SurfaceConfiguration, Texture, TextureFormat, TextureUsages, TextureView,
+};
+
+/// The name of the surface texture, which every graph can write to without declaring it.
+pub const SURFACE: &str = "surface";
+
+/// How large a transient texture is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureSize {
+    /// The surface size multiplied by a scale, e.g. `0.5` for a half resolution bloom target.
+    Relative(f32),
+    Absolute {
+        width: u32,
+        height: u32,
+    },
+}
+
+impl TextureSize {
+    fn resolve(self, width: u32, height: u32) -> (u32, u32) {
+        match self {
+            TextureSize::Relative(scale) => (
+                ((width as f32 * scale).round() as u32).max(1),
+                ((height as f32 * scale).round() as u32).max(1),
+            ),
+            TextureSize::Absolute { width, height } => (width.max(1), height.max(1)),
+        }
+    }
+}
+
+/// Describes a texture owned by a [`RenderGraph`].
+#[derive(TypedBuilder, Debug, Clone, Copy, PartialEq)]
+pub struct TextureDesc {
+    pub format: TextureFormat,
+    #[builder(default = TextureSize::Relative(1.0))]
+    pub size: TextureSize,
+    #[builder(default = 1)]
+    pub sample_count: u32,
+    #[builder(default = TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING)]
+    pub usage: TextureUsages,
+    /// Keeps the texture from being shared with other ones, so its contents are still there the
+    /// next frame, e.g. for temporal effects.
+    #[builder(default)]
+    pub persistent: bool,
+}
+
+/// Describes a buffer owned by a [`RenderGraph`]. Buffers are never shared, and keep their
+/// contents between frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferDesc {
+    pub size: u64,
+    pub usage: BufferUsages,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PassKind {
+    Render,
+    Compute,
+}
+
+struct ColorWrite {
+    texture: String,
+    resolve: Option<String>,
+    ops: Operations<Color>,
+}
+
+struct DepthWrite {
+    texture: String,
+    ops: Operations<f32>,
+}
+
+/// A pass of a [`RenderGraph`], and the resources it reads and writes.
+pub struct PassDesc {
+    name: String,
+    kind: PassKind,
+    reads: Vec<String>,
+    writes: Vec<String>,
+    color: Vec<ColorWrite>,
+    depth: Option<DepthWrite>,
+}
+
+impl PassDesc {
+    pub fn render(name: impl Into<String>) -> Self {
+        Self::new(name.into(), PassKind::Render)
+    }
+
+    pub fn compute(name: impl Into<String>) -> Self {
+        Self::new(name.into(), PassKind::Compute)
+    }
+
+    fn new(name: String, kind: PassKind) -> Self {
+        Self {
+            name,
+            kind,
+            reads: Vec::new(),
+            writes: Vec::new(),
+            color: Vec::new(),
+            depth: None,
+        }
+    }
+
+    /// Reads a texture or buffer, e.g. by sampling it, so the pass runs after the pass writing it,
+    /// see [`RenderGraph`].
+    pub fn read(mut self, resource: impl Into<String>) -> Self {
+        self.reads.push(resource.into());
+        self
+    }
+
+    /// Writes a texture or buffer other than through an attachment, e.g. as storage.
+    pub fn write(mut self, resource: impl Into<String>) -> Self {
+        self.writes.push(resource.into());
+        self
+    }
+
+    /// Renders into `texture`, the color attachments are in the order they're added.
+    pub fn color(mut self, texture: impl Into<String>, ops: Operations<Color>) -> Self {
+        self.color.push(ColorWrite {
+            texture: texture.into(),
+            resolve: None,
+            ops,
+        });
+        self
+    }
+
+    /// Renders into the multisampled `texture`, and resolves it into `resolve`.
+    pub fn color_resolved(
+        mut self,
+        texture: impl Into<String>,
+        resolve: impl Into<String>,
+        ops: Operations<Color>,
+    ) -> Self {
+        self.color.push(ColorWrite {
+            texture: texture.into(),
+            resolve: Some(resolve.into()),
+            ops,
+        });
+        self
+    }
+
+    pub fn depth(mut self, texture: impl Into<String>, ops: Operations<f32>) -> Self {
+        self.depth = Some(DepthWrite {
+            texture: texture.into(),
+            ops,
+        });
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> PassKind {
+        self.kind
+    }
+
+    fn written(&self) -> impl Iterator<Item = &str> {
+        let color = self
+            .color
+            .iter()
+            .flat_map(|color| std::iter::once(&color.texture).chain(&color.resolve));
+
+        self.writes
+            .iter()
+            .chain(color)
+            .chain(self.depth.as_ref().map(|depth| &depth.texture))
+            .map(String::as_str)
+    }
+
+    fn used(&self) -> impl Iterator<Item = &str> {
+        self.reads.iter().map(String::as_str).chain(self.written())
+    }
+}
+
+struct PhysicalTexture {
+    desc: TextureDesc,
+    width: u32,
+    height: u32,
+    // index into the pass order of the last pass using it.
+    last_use: usize,
+    texture: Texture,
+    view: TextureView,
+}
+
+/// Passes, and the textures and buffers they read and write.
+///
+/// The graph orders passes so a pass reading a resource runs after the last pass added before it
+/// that writes the resource, and before the passes added after it that write it again. Passes
+/// otherwise run in the order they were added. Textures are created at the
+/// surface size when the graph is compiled, and textures that are never in use at the same time
+/// share the same memory.
+///
+/// ```ignore
+/// let mut graph = RenderGraph::new();
+/// graph
+///     .add_texture("hdr", TextureDesc::builder().format(TextureFormat::Rgba16Float).build())
+///     .add_pass(PassDesc::render("scene").color("hdr", ops))
+///     .add_pass(PassDesc::render("tonemap").read("hdr").color(SURFACE, ops));
+///
+/// let mut execution = graph.execute(device, config, encoder, view)?;
+///
+/// while let Some(mut pass) = execution.next() {
+///     match pass.name() {
+///         "scene" => { /* draw into pass.render_pass() */ }
+///         "tonemap" => { /* sample pass.texture_view("hdr") */ }
+///         _ => (),
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct RenderGraph {
+    textures: BTreeMap<String, TextureDesc>,
+    buffers: BTreeMap<String, BufferDesc>,
+    passes: Vec<PassDesc>,
+    order: Vec<usize>,
+    compiled: Option<(u32, u32)>,
+    physical: Vec<PhysicalTexture>,
+    assigned: HashMap<String, usize>,
+    allocated_buffers: HashMap<String, (BufferDesc, Buffer)>,
+    generation: u64,
+}
+
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_texture(&mut self, name: impl Into<String>, desc: TextureDesc) -> &mut Self {
+        self.textures.insert(name.into(), desc);
+        self.compiled = None;
+        self
+    }
+
+    pub fn add_buffer(&mut self, name: impl Into<String>, desc: BufferDesc) -> &mut Self {
+        self.buffers.insert(name.into(), desc);
+        self.compiled = None;
+        self
+    }
+
+    pub fn add_pass(&mut self, pass: PassDesc) -> &mut Self {
+        self.passes.push(pass);
+        self.compiled = None;
+        self
+    }
+
+    /// Orders the passes and creates the resources at the size of `config`. This happens in
+    /// [`RenderGraph::execute`] as well, whenever the graph or the surface size changed.
+    pub fn compile(
+        &mut self,
+        device: &Device,
+        config: &SurfaceConfiguration,
+    ) -> anyhow::Result<()> {
+        self.validate()?;
+        self.order = self.sort()?;
+        self.allocate(device, config.width, config.height);
+        self.compiled = Some((config.width, config.height));
+
+        Ok(())
+    }
+
+    /// Compiles the graph if needed, and starts recording its passes into `encoder`, with `view`
+    /// as the [`SURFACE`] texture.
+    pub fn execute<'g>(
+        &'g mut self,
+        device: &Device,
+        config: &SurfaceConfiguration,
+        encoder: &'g mut CommandEncoder,
+        view: &'g TextureView,
+    ) -> anyhow::Result<GraphExecution<'g>> {
+        if self.compiled != Some((config.width, config.height)) {
+            self.compile(device, config)?;
+        }
+
+        Ok(GraphExecution {
+            graph: self,
+            encoder,
+            surface: view,
+            next: 0,
+        })
+    }
+
+    /// The names of the passes in the order they run, as of the last compile.
+    pub fn order(&self) -> impl Iterator<Item = &str> {
+        self.order.iter().map(|idx| self.passes[*idx].name())
+    }
+
+    /// A texture of the graph, if it's used by any pass and the graph has been compiled.
+    pub fn texture(&self, name: &str) -> Option<&Texture> {
+        self.assigned
+            .get(name)
+            .map(|idx| &self.physical[*idx].texture)
+    }
+
+    pub fn texture_view(&self, name: &str) -> Option<&TextureView> {
+        self.assigned.get(name).map(|idx| &self.physical[*idx].view)
+    }
+
+    pub fn buffer(&self, name: &str) -> Option<&Buffer> {
+        self.allocated_buffers.get(name).map(|(_, buffer)| buffer)
+    }
+
+    /// Goes up every time textures are recreated, so bind groups using them know to follow.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The passes and resources as a Graphviz DOT graph, with passes numbered in the order they run.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph render_graph {\n    rankdir=LR;\n");
+        let position = |idx: usize| self.order.iter().position(|ordered| *ordered == idx);
+
+        let resources = self
+            .passes
+            .iter()
+            .flat_map(PassDesc::used)
+            .collect::<BTreeSet<_>>();
+
+        for resource in resources {
+            let label = match (self.textures.get(resource), self.buffers.get(resource)) {
+                (Some(desc), _) => {
+                    let size = match desc.size {
+                        TextureSize::Relative(scale) => format!("{scale}x"),
+                        TextureSize::Absolute { width, height } => format!("{width}x{height}"),
+                    };
+                    format!("{resource}\\n{:?} {size}", desc.format)
+                }
+                (None, Some(desc)) => format!("{resource}\\n{} bytes", desc.size),
+                (None, None) => resource.to_string(),
+            };
+
+            let _ = writeln!(
+                dot,
+                "    \"res:{resource}\" [shape=ellipse, label=\"{label}\"];"
+            );
+        }
+
+        for (idx, pass) in self.passes.iter().enumerate() {
+            let label = match position(idx) {
+                Some(position) => format!("{position}: {}", pass.name),
+                None => pass.name.clone(),
+            };
+            let shape = match pass.kind {
+                PassKind::Render => "box",
+                PassKind::Compute => "hexagon",
+            };
+
+            let _ = writeln!(
+                dot,
+                "    \"pass:{}\" [shape={shape}, label=\"{label}\"];",
+                pass.name
+            );
+
+            for read in &pass.reads {
+                let _ = writeln!(dot, "    \"res:{read}\" -> \"pass:{}\";", pass.name);
+            }
+
+            for written in pass.written() {
+                let _ = writeln!(dot, "    \"pass:{}\" -> \"res:{written}\";", pass.name);
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        let mut names = BTreeSet::new();
+
+        for pass in &self.passes {
+            if !names.insert(pass.name.as_str()) {
+                bail!("there is more than one pass called {:?}", pass.name);
+            }
+
+            for resource in pass.used() {
+                let declared = resource == SURFACE
+                    || self.textures.contains_key(resource)
+                    || self.buffers.contains_key(resource);
+
+                if !declared {
+                    bail!(
+                        "pass {:?} uses {resource:?}, which isn't declared",
+                        pass.name
+                    );
+                }
+            }
+
+            let attachments = pass
+                .color
+                .iter()
+                .flat_map(|color| std::iter::once(&color.texture).chain(&color.resolve))
+                .chain(pass.depth.as_ref().map(|depth| &depth.texture));
+
+            for attachment in attachments {
+                if self.buffers.contains_key(attachment) {
+                    bail!("pass {:?} attaches the buffer {attachment:?}", pass.name);
+                }
+
+                if pass.kind == PassKind::Compute {
+                    bail!("compute pass {:?} can't have attachments", pass.name);
+                }
+            }
+
+            if pass.depth.as_ref().map(|depth| depth.texture.as_str()) == Some(SURFACE) {
+                bail!(
+                    "pass {:?} uses the surface as a depth attachment",
+                    pass.name
+                );
+            }
+        }
+
+        if let Some(name) = self
+            .textures
+            .keys()
+            .find(|name| self.buffers.contains_key(*name))
+        {
+            bail!("{name:?} is declared as both a texture and a buffer");
+        }
+
+        Ok(())
+    }
+
+    fn sort(&self) -> anyhow::Result<Vec<usize>> {
+        let mut edges = vec![BTreeSet::new(); self.passes.len()];
+        let mut incoming = vec![0usize; self.passes.len()];
+        let mut add_edge = |from: usize, to: usize| {
+            if from != to && edges[from].insert(to) {
+                incoming[to] += 1;
+            }
+        };
+
+        let writers_of = |resource: &str| {
+            (0..self.passes.len())
+                .filter(|idx| {
+                    self.passes[*idx]
+                        .written()
+                        .any(|written| written == resource)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        for (idx, pass) in self.passes.iter().enumerate() {
+            // writers of the same resource keep the order they were added in.
+            for written in pass.written() {
+                for writer in writers_of(written)
+                    .into_iter()
+                    .filter(|writer| *writer < idx)
+                {
+                    add_edge(writer, idx);
+                }
+            }
+
+            for read in &pass.reads {
+                let writers = writers_of(read);
+
+                // a read sees the latest write added before it, and comes before the writes
+                // added after it. reads of something only written later wait for all of it.
+                match writers.iter().rev().find(|writer| **writer < idx) {
+                    Some(&latest) => {
+                        add_edge(latest, idx);
+
+                        for &writer in writers.iter().filter(|writer| **writer > idx) {
+                            add_edge(idx, writer);
+                        }
+                    }
+                    None => {
+                        for writer in writers {
+                            add_edge(writer, idx);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut ready = (0..self.passes.len())
+            .filter(|idx| incoming[*idx] == 0)
+            .collect::<BTreeSet<_>>();
+        let mut order = Vec::with_capacity(self.passes.len());
+
+        while let Some(idx) = ready.iter().next().copied() {
+            ready.remove(&idx);
+            order.push(idx);
+
+            for &to in &edges[idx] {
+                incoming[to] -= 1;
+
+                if incoming[to] == 0 {
+                    ready.insert(to);
+                }
+            }
+        }
+
+        if order.len() != self.passes.len() {
+            let cycle = (0..self.passes.len())
+                .filter(|idx| !order.contains(idx))
+                .map(|idx| self.passes[idx].name.as_str())
+                .collect::<Vec<_>>();
+
+            bail!("passes {cycle:?} read each other's output");
+        }
+
+        Ok(order)
+    }
+
+    fn allocate(&mut self, device: &Device, width: u32, height: u32) {
+        let mut lifetimes = BTreeMap::new();
+
+        for (position, idx) in self.order.iter().enumerate() {
+            for resource in self.passes[*idx].used() {
+                if let Some((name, desc)) = self.textures.get_key_value(resource) {
+                    let (first, last, _) = lifetimes
+                        .entry(name.as_str())
+                        .or_insert((position, position, *desc));
+                    *first = (*first).min(position);
+                    *last = (*last).max(position);
+                }
+            }
+        }
+
+        let mut lifetimes = lifetimes.into_iter().collect::<Vec<_>>();
+        lifetimes.sort_by_key(|(_, (first, _, _))| *first);
+
+        self.physical.clear();
+        self.assigned.clear();
+
+        for (name, (first, last, desc)) in lifetimes {
+            let (texture_width, texture_height) = desc.size.resolve(width, height);
+
+            // reuse a texture that nothing needs anymore by the time this one is first written.
+            let reusable = match desc.persistent {
+                true => None,
+                false => self.physical.iter().position(|physical| {
+                    !physical.desc.persistent
+                        && physical.last_use < first
+                        && physical.width == texture_width
+                        && physical.height == texture_height
+                        && physical.desc.format == desc.format
+                        && physical.desc.sample_count == desc.sample_count
+                        && physical.desc.usage == desc.usage
+                }),
+            };
+
+            let idx = match reusable {
+                Some(idx) => {
+                    self.physical[idx].last_use = last;
+                    idx
+                }
+                None => {
+                    let texture = device.create_texture(&wgpu::TextureDescriptor {
+                        label: Some(name),
+                        size: wgpu::Extent3d {
+                            width: texture_width,
+                            height: texture_height,
+                            depth_or_array_layers: 1,
+                        },
+                        mip_level_count: 1,
+                        sample_count: desc.sample_count,
+                        dimension: wgpu::TextureDimension::D2,
+                        format: desc.format,
+                        usage: desc.usage,
+                        view_formats: &[],
+                    });
+                    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+                    self.physical.push(PhysicalTexture {
+                        desc,
+                        width: texture_width,
+                        height: texture_height,
+                        last_use: last,
+                        texture,
+                        view,
+                    });
+                    self.physical.len() - 1
+                }
+            };
+
+            self.assigned.insert(name.to_string(), idx);
+        }
+
+        self.allocated_buffers
+            .retain(|name, (desc, _)| self.buffers.get(name) == Some(&*desc));
+
+        for (name, desc) in &self.buffers {
+            self.allocated_buffers
+                .entry(name.clone())
+                .or_insert_with(|| {
+                    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some(name),
+                        size: desc.size,
+                        usage: desc.usage,
+                        mapped_at_creation: false,
+                    });
+                    (*desc, buffer)
+                });
+        }
+
+        self.generation += 1;
+
+        log::debug!(
+            "render graph compiled: {} passes, {} textures in {} allocations",
+            self.order.len(),
+            self.assigned.len(),
+            self.physical.len()
+        );
+    }
+
+    fn view<'a>(&'a self, name: &str, surface: &'a TextureView) -> &'a TextureView {
+        match name {
+            SURFACE => surface,
+            // every used texture is assigned when compiling.
+            _ => &self.physical[self.assigned[name]].view,
+        }
+    }
+}
+
+/// The passes of a [`RenderGraph`] being recorded, see [`RenderGraph::execute`].
+pub struct GraphExecution<'g> {
+    graph: &'g RenderGraph,
+    encoder: &'g mut CommandEncoder,
+    surface: &'g TextureView,
+    next: usize,
+}
+
+impl<'g> GraphExecution<'g> {
+    /// Begins the next pass, with its attachments set up. It has to be dropped before the one
+    /// after it can begin.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<GraphPass<'_>> {
+        let (graph, surface) = (self.graph, self.surface);
+        let pass = &graph.passes[*graph.order.get(self.next)?];
+        self.next += 1;
+
+        let encoder = match pass.kind {
+            PassKind::Render => {
+                let color_attachments = pass
+                    .color
+                    .iter()
+                    .map(|color| {
+                        Some(wgpu::RenderPassColorAttachment {
+                            view: graph.view(&color.texture, surface),
+                            resolve_target: color
+                                .resolve
+                                .as_deref()
+                                .map(|resolve| graph.view(resolve, surface)),
+                            ops: color.ops,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                let depth_stencil_attachment =
+                    pass.depth
+                        .as_ref()
+                        .map(|depth| wgpu::RenderPassDepthStencilAttachment {
+                            view: graph.view(&depth.texture, surface),
+                            depth_ops: Some(depth.ops),
+                            stencil_ops: None,
+                        });
+
+                PassEncoder::Render(self.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some(&pass.name),
+                    color_attachments: &color_attachments,
+                    depth_stencil_attachment,
+                }))
+            }
+            PassKind::Compute => PassEncoder::Compute(self.encoder.begin_compute_pass(
+                &wgpu::ComputePassDescriptor {
+                    label: Some(&pass.name),
+                },
+            )),
+        };
+
+        Some(GraphPass {
+            name: &pass.name,
+            graph,
+            surface,
+            encoder,
+        })
+    }
+}
+
+/// The wgpu pass a [`GraphPass`] records into.
+pub enum PassEncoder<'p> {
+    Render(RenderPass<'p>),
+    Compute(ComputePass<'p>),
+}
+
+/// A pass of a [`RenderGraph`] being recorded.
+pub struct GraphPass<'p> {
+    name: &'p str,
+    graph: &'p RenderGraph,
+    surface: &'p TextureView,
+    encoder: PassEncoder<'p>,
+}
+
+impl<'p> GraphPass<'p> {
+    pub fn name(&self) -> &'p str {
+        self.name
+    }
+
+    pub fn encoder(&mut self) -> &mut PassEncoder<'p> {
+        &mut self.encoder
+    }
+
+    /// The render pass, if this is a [`PassKind::Render`] pass.
+    pub fn render_pass(&mut self) -> Option<&mut RenderPass<'p>> {
+        match &mut self.encoder {
+            PassEncoder::Render(pass) => Some(pass),
+            PassEncoder::Compute(_) => None,
+        }
+    }
+
+    /// The compute pass, if this is a [`PassKind::Compute`] pass.
+    pub fn compute_pass(&mut self) -> Option<&mut ComputePass<'p>> {
+        match &mut self.encoder {
+            PassEncoder::Compute(pass) => Some(pass),
+            PassEncoder::Render(_) => None,
+        }
+    }
+
+    /// A texture of the graph, or the surface for [`SURFACE`].
+    pub fn texture_view(&self, name: &str) -> Option<&'p TextureView> {
+        match name {
+            SURFACE => Some(self.surface),
+            _ => self.graph.texture_view(name),
+        }
+    }
+
+    pub fn buffer(&self, name: &str) -> Option<&'p Buffer> {
+        self.graph.buffer(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(graph: &RenderGraph) -> Vec<&str> {
+        graph
+            .sort()
+            .unwrap()
+            .into_iter()
+            .map(|idx| graph.passes[idx].name.as_str())
+            .collect()
+    }
+
+    fn ops() -> Operations<Color> {
+        Operations {
+            load: wgpu::LoadOp::Clear(Color::BLACK),
+            store: true,
+        }
+    }
+
+    fn color(format: TextureFormat) -> TextureDesc {
+        TextureDesc::builder().format(format).build()
+    }
+
+    /// `a` is done with before `c` is written, and `b` lives alongside both of them.
+    fn chain(c: TextureDesc) -> RenderGraph {
+        let mut graph = RenderGraph::new();
+        graph
+            .add_texture("a", color(TextureFormat::Rgba8Unorm))
+            .add_texture("b", color(TextureFormat::Rgba8Unorm))
+            .add_texture("c", c)
+            .add_pass(PassDesc::render("first").color("a", ops()))
+            .add_pass(PassDesc::render("second").read("a").color("b", ops()))
+            .add_pass(PassDesc::render("third").read("b").color("c", ops()))
+            .add_pass(PassDesc::render("last").read("c").color(SURFACE, ops()));
+
+        graph
+    }
+
+    fn compile(graph: &mut RenderGraph) {
+        let (_instance, _adapter, device, _queue) = pollster::block_on(
+            crate::render::headless::init_headless_wgpu(&Default::default()),
+        )
+        .unwrap();
+        let config = SurfaceConfiguration {
+            usage: TextureUsages::RENDER_ATTACHMENT,
+            format: TextureFormat::Rgba8UnormSrgb,
+            width: 8,
+            height: 8,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+        };
+
+        graph.compile(&device, &config).unwrap();
+    }
+
+    #[test]
+    fn textures_share_memory_when_their_lifetimes_dont_overlap() {
+        let mut graph = chain(color(TextureFormat::Rgba8Unorm));
+        compile(&mut graph);
+
+        assert_eq!(graph.assigned["a"], graph.assigned["c"]);
+        assert_ne!(graph.assigned["a"], graph.assigned["b"]);
+        assert_ne!(graph.assigned["b"], graph.assigned["c"]);
+        assert_eq!(graph.physical.len(), 2);
+    }
+
+    #[test]
+    fn textures_only_share_memory_when_compatible() {
+        let persistent = TextureDesc::builder()
+            .format(TextureFormat::Rgba8Unorm)
+            .persistent(true)
+            .build();
+        let half_size = TextureDesc::builder()
+            .format(TextureFormat::Rgba8Unorm)
+            .size(TextureSize::Relative(0.5))
+            .build();
+
+        for c in [persistent, half_size, color(TextureFormat::Rgba16Float)] {
+            let mut graph = chain(c);
+            compile(&mut graph);
+
+            assert_ne!(graph.assigned["a"], graph.assigned["c"], "{c:?}");
+            assert_eq!(graph.physical.len(), 3);
+        }
+    }
+
+    #[test]
+    fn dot_numbers_passes_in_order() {
+        let mut graph = chain(color(TextureFormat::Rgba8Unorm));
+        graph.add_buffer(
+            "params",
+            BufferDesc {
+                size: 64,
+                usage: BufferUsages::STORAGE,
+            },
+        );
+        graph.add_pass(PassDesc::compute("simulate").write("params"));
+        graph.order = graph.sort().unwrap();
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph render_graph {\n"));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("\"pass:first\" [shape=box, label=\"0: first\"];"));
+        assert!(dot.contains("\"pass:simulate\" [shape=hexagon, label=\"4: simulate\"];"));
+        assert!(dot.contains("\"res:a\" [shape=ellipse, label=\"a\\nRgba8Unorm 1x\"];"));
+        assert!(dot.contains("\"res:params\" [shape=ellipse, label=\"params\\n64 bytes\"];"));
+        assert!(dot.contains("\"res:surface\" [shape=ellipse, label=\"surface\"];"));
+        assert!(dot.contains("\"res:a\" -> \"pass:second\";"));
+        assert!(dot.contains("\"pass:second\" -> \"res:b\";"));
+    }
+
+    #[test]
+    fn reads_see_the_write_added_before_them() {
+        let mut graph = RenderGraph::new();
+        graph
+            .add_pass(PassDesc::compute("first").write("data"))
+            .add_pass(PassDesc::compute("read").read("data"))
+            .add_pass(PassDesc::compute("second").write("data"));
+
+        assert_eq!(order(&graph), ["first", "read", "second"]);
+    }
+
+    #[test]
+    fn reads_wait_for_writes_added_after_them() {
+        let mut graph = RenderGraph::new();
+        graph
+            .add_pass(PassDesc::compute("read").read("data"))
+            .add_pass(PassDesc::compute("first").write("data"))
+            .add_pass(PassDesc::compute("second").write("data"));
+
+        assert_eq!(order(&graph), ["first", "second", "read"]);
+    }
+}
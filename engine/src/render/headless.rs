@@ -0,0 +1,339 @@
+use std::time::Duration;
+
+use typed_builder::TypedBuilder;
+use wgpu::InstanceDescriptor;
+use winit::event_loop::ControlFlow;
+
+use super::{
+    capture::{self, RgbaImage},
+    config::EngineConfig,
+    context::Context,
+    error::EngineError,
+    framework::{request_device, run_fixed_updates, Framework},
+    profiler::GpuProfiler,
+    replay::{Recording, Replay},
+    target::RenderTargets,
+    time::Time,
+};
+
+/// Options for rendering a [`Framework`] into an offscreen texture, without a window or surface.
+#[derive(TypedBuilder, Debug, Clone)]
+pub struct HeadlessOptions {
+    width: u32,
+    height: u32,
+    #[builder(default = wgpu::TextureFormat::Rgba8UnormSrgb)]
+    format: wgpu::TextureFormat,
+    /// Backends, features and limits are taken from here, while everything surface and window
+    /// related is ignored.
+    #[builder(default)]
+    engine_config: EngineConfig,
+    /// Runs with [`Time::fixed`] instead of the wall clock.
+    #[builder(default, setter(strip_option))]
+    fixed_delta: Option<Duration>,
+}
+
+impl HeadlessOptions {
+    pub(crate) fn surface_config(&self) -> wgpu::SurfaceConfiguration {
+        wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: self.format,
+            width: self.width.max(1),
+            height: self.height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+        }
+    }
+
+    pub(crate) fn time(&self) -> Time {
+        match self.fixed_delta {
+            Some(delta) => Time::fixed(delta),
+            None => Time::new(),
+        }
+    }
+}
+
+type Predicate<T> = Box<dyn FnMut(&T, usize) -> bool>;
+
+/// Decides when [`run_headless`] stops rendering.
+pub enum Until<T> {
+    /// Renders exactly this many frames.
+    Frames(usize),
+    /// Renders until the predicate returns `true`. It is given the framework and the
+    /// amount of frames rendered so far, and is checked before every frame.
+    Predicate(Predicate<T>),
+}
+
+impl<T> Until<T> {
+    pub fn predicate(predicate: impl FnMut(&T, usize) -> bool + 'static) -> Self {
+        Self::Predicate(Box::new(predicate))
+    }
+
+    fn is_done(&mut self, framework: &T, frames: usize) -> bool {
+        match self {
+            Until::Frames(count) => frames >= *count,
+            Until::Predicate(predicate) => predicate(framework, frames),
+        }
+    }
+}
+
+/// A [`Framework`] rendering into an offscreen texture.
+///
+/// Everything is left public so the rendered texture can be inspected after [`run_headless`] returns.
+/// By then [`Framework::shutdown`] has been called, so rendering more frames is up to the framework.
+pub struct HeadlessRun<T: Framework> {
+    pub framework: T,
+    pub instance: wgpu::Instance,
+    pub adapter: wgpu::Adapter,
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    pub config: wgpu::SurfaceConfiguration,
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub targets: RenderTargets,
+    pub context: Context,
+    pub frames: usize,
+}
+
+impl<T: Framework> HeadlessRun<T> {
+    /// Renders a single frame into the offscreen texture and waits for the GPU to finish it.
+    pub fn render_frame(&mut self) {
+        self.render_frame_with_ticks(None);
+    }
+
+    fn render_frame_with_ticks(&mut self, ticks: Option<u32>) {
+        run_fixed_updates(&mut self.framework, &mut self.context, ticks);
+        self.context.uploads.process(&self.device, &self.queue);
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Headless Render Encoder"),
+            });
+
+        self.framework.render(
+            &mut self.context,
+            &mut encoder,
+            &self.view,
+            &self.targets,
+            &self.device,
+            &self.queue,
+        );
+        self.context.profiler.resolve(&self.device, &mut encoder);
+        self.queue.submit(std::iter::once(encoder.finish()));
+        self.device.poll(wgpu::Maintain::Wait);
+        self.context.profiler.end_frame(&self.device);
+
+        self.context.end_frame();
+        self.frames += 1;
+    }
+
+    /// Calls [`Framework::shutdown`] and waits for the GPU to finish what it submitted, like a
+    /// windowed run does when it exits.
+    fn shutdown(&mut self) {
+        self.framework.shutdown(&mut self.context);
+        self.device.poll(wgpu::Maintain::Wait);
+    }
+
+    /// Reads the last rendered frame back to the CPU.
+    pub fn capture(&self) -> anyhow::Result<RgbaImage> {
+        capture::capture_texture(
+            &self.device,
+            &self.queue,
+            &self.texture,
+            self.config.format,
+            self.config.width,
+            self.config.height,
+        )
+    }
+}
+
+pub async fn init_headless_wgpu(
+    engine_config: &EngineConfig,
+) -> Result<(wgpu::Instance, wgpu::Adapter, wgpu::Device, wgpu::Queue), EngineError> {
+    let _ = env_logger::try_init();
+
+    let instance = wgpu::Instance::new(InstanceDescriptor {
+        backends: engine_config.backends(),
+        ..Default::default()
+    });
+
+    let mut options = wgpu::RequestAdapterOptions {
+        power_preference: engine_config.power_preference(),
+        compatible_surface: None,
+        force_fallback_adapter: engine_config.force_fallback_adapter,
+    };
+
+    let adapter = match instance.request_adapter(&options).await {
+        Some(adapter) => adapter,
+        None => {
+            // build boxes rarely have a GPU, so fall back to a software adapter if we haven't already.
+            options.force_fallback_adapter = true;
+            instance
+                .request_adapter(&options)
+                .await
+                .ok_or(EngineError::NoAdapter)?
+        }
+    };
+
+    let (device, queue) = request_device(&adapter, engine_config).await?;
+
+    Ok((instance, adapter, device, queue))
+}
+
+/// Renders `T` into an offscreen texture of the configured size, until `until` is satisfied.
+/// [`Framework::shutdown`] is called before returning.
+///
+/// The view handed to [`Framework::render`] is a plain texture view, so frameworks don't need to know
+/// whether they are drawing into a window or not.
+pub fn run_headless<T: Framework>(
+    options: HeadlessOptions,
+    mut until: Until<T>,
+) -> Result<HeadlessRun<T>, EngineError> {
+    let mut run = start_headless::<T>(options)?;
+
+    while !until.is_done(&run.framework, run.frames) && !run.context.window.exit_requested() {
+        run.render_frame();
+    }
+
+    run.shutdown();
+    Ok(run)
+}
+
+/// Creates the device, the offscreen texture and the framework, without rendering anything.
+fn start_headless<T: Framework>(options: HeadlessOptions) -> Result<HeadlessRun<T>, EngineError> {
+    let (instance, adapter, device, queue) =
+        pollster::block_on(init_headless_wgpu(&options.engine_config))?;
+
+    let config = options.surface_config();
+    let texture = capture::create_capture_texture(&device, &config);
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let targets = RenderTargets::new(
+        &adapter,
+        &device,
+        &config,
+        options.engine_config.sample_count,
+        options.engine_config.depth_format(),
+    )?;
+
+    let framework =
+        T::init(&config, &targets, &adapter, &device, &queue).map_err(EngineError::Init)?;
+
+    let mut context = Context::new(options.time());
+    context.configure(&options.engine_config);
+    context.input.set_actions(framework.action_map());
+
+    let tick_rate = framework.tick_rate();
+
+    if !tick_rate.is_valid() {
+        return Err(EngineError::InvalidTickRate(tick_rate.hz));
+    }
+
+    if options.engine_config.gpu_profiling {
+        context.profiler = GpuProfiler::new(&device, &queue);
+    }
+
+    Ok(HeadlessRun {
+        framework,
+        instance,
+        adapter,
+        device,
+        queue,
+        config,
+        texture,
+        view,
+        targets,
+        context,
+        frames: 0,
+    })
+}
+
+/// Like [`run_headless`], but renders one frame for every frame of `recording`, replaying its
+/// input and fixed-step ticks with a simulated clock. [`Framework::shutdown`] is called before
+/// returning.
+pub fn replay_headless<T: Framework>(
+    options: HeadlessOptions,
+    recording: Recording,
+) -> Result<HeadlessRun<T>, EngineError> {
+    let mut run = start_headless::<T>(options)?;
+    let mut replay = Replay::new(recording, run.framework.tick_rate().hz);
+    let mut control_flow = ControlFlow::Poll;
+
+    while !run.context.window.exit_requested() && control_flow != ControlFlow::Exit {
+        let Some(ticks) =
+            replay.next_frame(&mut run.framework, &mut run.context, &mut control_flow)
+        else {
+            break;
+        };
+
+        run.render_frame_with_ticks(Some(ticks));
+    }
+
+    run.shutdown();
+    Ok(run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Lifecycle {
+        rendered: usize,
+        shut_down: bool,
+    }
+
+    impl Framework for Lifecycle {
+        fn init(
+            _config: &wgpu::SurfaceConfiguration,
+            _targets: &RenderTargets,
+            _adapter: &wgpu::Adapter,
+            _device: &wgpu::Device,
+            _queue: &wgpu::Queue,
+        ) -> anyhow::Result<Self> {
+            Ok(Self::default())
+        }
+
+        fn render(
+            &mut self,
+            _ctx: &mut Context,
+            _encoder: &mut wgpu::CommandEncoder,
+            _view: &wgpu::TextureView,
+            _targets: &RenderTargets,
+            _device: &wgpu::Device,
+            _queue: &wgpu::Queue,
+        ) {
+            assert!(!self.shut_down, "rendered after shutting down");
+            self.rendered += 1;
+        }
+
+        fn shutdown(&mut self, _ctx: &mut Context) {
+            self.shut_down = true;
+        }
+    }
+
+    fn options() -> HeadlessOptions {
+        HeadlessOptions::builder().width(4).height(4).build()
+    }
+
+    #[test]
+    fn shuts_down_after_the_last_frame() {
+        let run = run_headless::<Lifecycle>(options(), Until::Frames(2)).unwrap();
+
+        assert_eq!(run.framework.rendered, 2);
+        assert!(run.framework.shut_down);
+    }
+
+    #[test]
+    fn shuts_down_after_a_replay() {
+        let recording = Recording {
+            tick_hz: 60.0,
+            frames: vec![Default::default(); 3],
+        };
+        let run = replay_headless::<Lifecycle>(options(), recording).unwrap();
+
+        assert_eq!(run.framework.rendered, 3);
+        assert!(run.framework.shut_down);
+    }
+}
@@ -0,0 +1,539 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::Path,
+};
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use winit::event::{
+    DeviceEvent, ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+    VirtualKeyCode, WindowEvent,
+};
+
+use super::{
+    config::{load_file, save_file},
+    replay::RecordedEvent,
+};
+
+/// How many pixels of a pixel-precise scroll (e.g. a touchpad) count as one line.
+pub const PIXELS_PER_LINE: f32 = 20.0;
+
+/// A key or mouse button an action or axis can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Binding {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+}
+
+/// Where the value of an axis comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(from = "AxisBindingRepr", into = "AxisBindingRepr")]
+pub enum AxisBinding {
+    /// `1.0` while `positive` is held, `-1.0` while `negative` is held, and `0.0` for both or neither.
+    Buttons {
+        positive: Binding,
+        negative: Binding,
+    },
+    /// Horizontal mouse motion this frame, multiplied by `scale`.
+    MouseX { scale: f32 },
+    /// Vertical mouse motion this frame, multiplied by `scale`.
+    MouseY { scale: f32 },
+    /// Vertical scroll this frame in lines, multiplied by `scale`.
+    Scroll { scale: f32 },
+}
+
+// toml can't write struct variants, so axis bindings are stored as newtype variants of structs.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum AxisBindingRepr {
+    Buttons(ButtonsRepr),
+    MouseX(ScaleRepr),
+    MouseY(ScaleRepr),
+    Scroll(ScaleRepr),
+}
+
+#[derive(Serialize, Deserialize)]
+struct ButtonsRepr {
+    positive: Binding,
+    negative: Binding,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ScaleRepr {
+    scale: f32,
+}
+
+impl From<AxisBindingRepr> for AxisBinding {
+    fn from(repr: AxisBindingRepr) -> Self {
+        match repr {
+            AxisBindingRepr::Buttons(ButtonsRepr { positive, negative }) => {
+                AxisBinding::Buttons { positive, negative }
+            }
+            AxisBindingRepr::MouseX(ScaleRepr { scale }) => AxisBinding::MouseX { scale },
+            AxisBindingRepr::MouseY(ScaleRepr { scale }) => AxisBinding::MouseY { scale },
+            AxisBindingRepr::Scroll(ScaleRepr { scale }) => AxisBinding::Scroll { scale },
+        }
+    }
+}
+
+impl From<AxisBinding> for AxisBindingRepr {
+    fn from(binding: AxisBinding) -> Self {
+        match binding {
+            AxisBinding::Buttons { positive, negative } => {
+                AxisBindingRepr::Buttons(ButtonsRepr { positive, negative })
+            }
+            AxisBinding::MouseX { scale } => AxisBindingRepr::MouseX(ScaleRepr { scale }),
+            AxisBinding::MouseY { scale } => AxisBindingRepr::MouseY(ScaleRepr { scale }),
+            AxisBinding::Scroll { scale } => AxisBindingRepr::Scroll(ScaleRepr { scale }),
+        }
+    }
+}
+
+/// Named actions and axes, and what they're bound to.
+///
+/// Can be saved to and loaded from a `.toml` or `.ron` file, so players can rebind them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActionMap {
+    pub actions: BTreeMap<String, Vec<Binding>>,
+    pub axes: BTreeMap<String, Vec<AxisBinding>>,
+}
+
+impl ActionMap {
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        load_file(path)
+    }
+
+    /// Like [`ActionMap::load`], but falls back to `default` if `path` doesn't exist.
+    pub fn load_or(path: impl AsRef<Path>, default: Self) -> anyhow::Result<Self> {
+        match path.as_ref().exists() {
+            true => Self::load(path),
+            false => Ok(default),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        save_file(self, path)
+    }
+
+    /// Adds `binding` to `action`, next to whatever it was already bound to.
+    pub fn bind(&mut self, action: impl Into<String>, binding: Binding) -> &mut Self {
+        let bindings = self.actions.entry(action.into()).or_default();
+
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+
+        self
+    }
+
+    /// Replaces every binding of `action` with `bindings`.
+    pub fn rebind(
+        &mut self,
+        action: impl Into<String>,
+        bindings: impl IntoIterator<Item = Binding>,
+    ) -> &mut Self {
+        self.actions
+            .insert(action.into(), bindings.into_iter().collect());
+        self
+    }
+
+    pub fn unbind(&mut self, action: &str, binding: Binding) -> &mut Self {
+        if let Some(bindings) = self.actions.get_mut(action) {
+            bindings.retain(|bound| *bound != binding);
+        }
+
+        self
+    }
+
+    pub fn bind_axis(&mut self, axis: impl Into<String>, binding: AxisBinding) -> &mut Self {
+        self.axes.entry(axis.into()).or_default().push(binding);
+        self
+    }
+
+    /// Replaces every binding of `axis` with `bindings`.
+    pub fn rebind_axis(
+        &mut self,
+        axis: impl Into<String>,
+        bindings: impl IntoIterator<Item = AxisBinding>,
+    ) -> &mut Self {
+        self.axes
+            .insert(axis.into(), bindings.into_iter().collect());
+        self
+    }
+
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn axis_bindings(&self, axis: &str) -> &[AxisBinding] {
+        self.axes.get(axis).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+/// Keyboard and mouse state, kept up to date by the framework runners.
+///
+/// "Pressed" and "released" only hold for the frame they happened in, so they are best checked in
+/// [`Framework::render`](super::framework::Framework::render). Ticks of
+/// [`Framework::update`](super::framework::Framework::update) run before rendering, and see the
+/// same frame's state.
+#[derive(Debug, Clone, Default)]
+pub struct Input {
+    held: HashSet<Binding>,
+    pressed: HashSet<Binding>,
+    released: HashSet<Binding>,
+    last_pressed: Option<Binding>,
+    mouse_delta: Vec2,
+    scroll_delta: Vec2,
+    cursor_position: Option<Vec2>,
+    modifiers: ModifiersState,
+    unfocused: bool,
+    actions: ActionMap,
+}
+
+impl Input {
+    pub fn handle_window_event(&mut self, event: &WindowEvent) {
+        if let Some(event) = RecordedEvent::from_window_event(event) {
+            self.handle_recorded_event(&event);
+        }
+    }
+
+    pub fn handle_device_event(&mut self, event: &DeviceEvent) {
+        if let Some(event) = RecordedEvent::from_device_event(event) {
+            self.handle_recorded_event(&event);
+        }
+    }
+
+    /// Handles an event the way the window or device event it was recorded from is handled.
+    pub fn handle_recorded_event(&mut self, event: &RecordedEvent) {
+        match event {
+            RecordedEvent::Keyboard(KeyboardInput {
+                state,
+                virtual_keycode: Some(key),
+                ..
+            }) => self.set(Binding::Key(*key), *state),
+            RecordedEvent::MouseInput { state, button } => {
+                self.set(Binding::Mouse(*button), *state)
+            }
+            RecordedEvent::MouseWheel(delta) => {
+                self.scroll_delta += match delta {
+                    MouseScrollDelta::LineDelta(x, y) => Vec2::new(*x, *y),
+                    MouseScrollDelta::PixelDelta(position) => {
+                        Vec2::new(position.x as f32, position.y as f32) / PIXELS_PER_LINE
+                    }
+                };
+            }
+            RecordedEvent::CursorMoved { x, y } => {
+                self.cursor_position = Some(Vec2::new(*x as f32, *y as f32));
+            }
+            RecordedEvent::CursorLeft => self.cursor_position = None,
+            RecordedEvent::ModifiersChanged(modifiers) => self.modifiers = *modifiers,
+            // key releases aren't delivered to unfocused windows, so nothing is held anymore.
+            RecordedEvent::Focused(false) => {
+                self.released.extend(self.held.drain());
+                self.modifiers = ModifiersState::empty();
+                self.unfocused = true;
+            }
+            RecordedEvent::Focused(true) => self.unfocused = false,
+            // device events keep arriving while another window has focus.
+            RecordedEvent::MouseMotion { x, y } if !self.unfocused => {
+                self.mouse_delta += Vec2::new(*x as f32, *y as f32);
+            }
+            _ => (),
+        }
+    }
+
+    /// Forgets everything that only holds for a single frame.
+    pub fn end_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.last_pressed = None;
+        self.mouse_delta = Vec2::ZERO;
+        self.scroll_delta = Vec2::ZERO;
+    }
+
+    pub fn held(&self, binding: Binding) -> bool {
+        self.held.contains(&binding)
+    }
+
+    pub fn pressed(&self, binding: Binding) -> bool {
+        self.pressed.contains(&binding)
+    }
+
+    pub fn released(&self, binding: Binding) -> bool {
+        self.released.contains(&binding)
+    }
+
+    pub fn key_held(&self, key: VirtualKeyCode) -> bool {
+        self.held(Binding::Key(key))
+    }
+
+    pub fn key_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.pressed(Binding::Key(key))
+    }
+
+    pub fn key_released(&self, key: VirtualKeyCode) -> bool {
+        self.released(Binding::Key(key))
+    }
+
+    pub fn mouse_held(&self, button: MouseButton) -> bool {
+        self.held(Binding::Mouse(button))
+    }
+
+    pub fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.pressed(Binding::Mouse(button))
+    }
+
+    pub fn mouse_released(&self, button: MouseButton) -> bool {
+        self.released(Binding::Mouse(button))
+    }
+
+    /// The last key or button pressed this frame, handy for letting players rebind actions.
+    pub fn last_pressed(&self) -> Option<Binding> {
+        self.last_pressed
+    }
+
+    /// Raw mouse motion this frame while the window has focus. Unlike the cursor position, this
+    /// keeps going when the cursor is grabbed or hits the window's edge, which suits mouse-look.
+    pub fn mouse_delta(&self) -> Vec2 {
+        self.mouse_delta
+    }
+
+    /// Scroll this frame in lines, see [`PIXELS_PER_LINE`].
+    pub fn scroll_delta(&self) -> Vec2 {
+        self.scroll_delta
+    }
+
+    /// Cursor position in physical pixels from the window's top left, if the cursor is in the window.
+    pub fn cursor_position(&self) -> Option<Vec2> {
+        self.cursor_position
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    pub fn actions(&self) -> &ActionMap {
+        &self.actions
+    }
+
+    pub fn actions_mut(&mut self) -> &mut ActionMap {
+        &mut self.actions
+    }
+
+    pub fn set_actions(&mut self, actions: ActionMap) {
+        self.actions = actions;
+    }
+
+    /// Whether any binding of `action` is held.
+    pub fn action_held(&self, action: &str) -> bool {
+        self.actions
+            .bindings(action)
+            .iter()
+            .any(|binding| self.held(*binding))
+    }
+
+    /// Whether any binding of `action` was pressed this frame.
+    pub fn action_pressed(&self, action: &str) -> bool {
+        self.actions
+            .bindings(action)
+            .iter()
+            .any(|binding| self.pressed(*binding))
+    }
+
+    /// Whether any binding of `action` was released this frame.
+    pub fn action_released(&self, action: &str) -> bool {
+        self.actions
+            .bindings(action)
+            .iter()
+            .any(|binding| self.released(*binding))
+    }
+
+    /// The sum of every binding of `axis`. Button axes are clamped to `-1.0..=1.0` together,
+    /// mouse and scroll axes aren't.
+    pub fn axis(&self, axis: &str) -> f32 {
+        let mut buttons = 0.0;
+        let mut analog = 0.0;
+
+        for binding in self.actions.axis_bindings(axis) {
+            match *binding {
+                AxisBinding::Buttons { positive, negative } => {
+                    buttons += self.held(positive) as i32 as f32;
+                    buttons -= self.held(negative) as i32 as f32;
+                }
+                AxisBinding::MouseX { scale } => analog += self.mouse_delta.x * scale,
+                AxisBinding::MouseY { scale } => analog += self.mouse_delta.y * scale,
+                AxisBinding::Scroll { scale } => analog += self.scroll_delta.y * scale,
+            }
+        }
+
+        buttons.clamp(-1.0, 1.0) + analog
+    }
+
+    fn set(&mut self, binding: Binding, state: ElementState) {
+        match state {
+            ElementState::Pressed => {
+                // key repeats are delivered as presses too, but aren't new presses.
+                if self.held.insert(binding) {
+                    self.pressed.insert(binding);
+                    self.last_pressed = Some(binding);
+                }
+            }
+            ElementState::Released => {
+                if self.held.remove(&binding) {
+                    self.released.insert(binding);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[allow(deprecated)]
+    fn key(input: &mut Input, key: VirtualKeyCode, state: ElementState) {
+        input.handle_recorded_event(&RecordedEvent::Keyboard(KeyboardInput {
+            scancode: 0,
+            state,
+            virtual_keycode: Some(key),
+            modifiers: ModifiersState::empty(),
+        }));
+    }
+
+    fn move_x() -> ActionMap {
+        let mut actions = ActionMap::default();
+        actions
+            .bind("jump", Binding::Key(VirtualKeyCode::Space))
+            .bind("jump", Binding::Mouse(MouseButton::Right))
+            .bind_axis(
+                "move_x",
+                AxisBinding::Buttons {
+                    positive: Binding::Key(VirtualKeyCode::D),
+                    negative: Binding::Key(VirtualKeyCode::A),
+                },
+            )
+            .bind_axis("look_x", AxisBinding::MouseX { scale: 0.5 })
+            .bind_axis("zoom", AxisBinding::Scroll { scale: -1.0 });
+
+        actions
+    }
+
+    #[test]
+    fn binds_without_duplicates() {
+        let mut actions = move_x();
+        actions.bind("jump", Binding::Key(VirtualKeyCode::Space));
+        assert_eq!(
+            actions.bindings("jump"),
+            [
+                Binding::Key(VirtualKeyCode::Space),
+                Binding::Mouse(MouseButton::Right)
+            ]
+        );
+
+        actions.unbind("jump", Binding::Mouse(MouseButton::Right));
+        assert_eq!(
+            actions.bindings("jump"),
+            [Binding::Key(VirtualKeyCode::Space)]
+        );
+
+        actions.rebind("jump", [Binding::Key(VirtualKeyCode::W)]);
+        assert_eq!(actions.bindings("jump"), [Binding::Key(VirtualKeyCode::W)]);
+        assert!(actions.bindings("crouch").is_empty());
+    }
+
+    #[test]
+    fn opposing_keys_cancel_out() {
+        let mut input = Input::default();
+        input.set_actions(move_x());
+        assert_eq!(input.axis("move_x"), 0.0);
+
+        key(&mut input, VirtualKeyCode::D, ElementState::Pressed);
+        assert_eq!(input.axis("move_x"), 1.0);
+
+        key(&mut input, VirtualKeyCode::A, ElementState::Pressed);
+        assert_eq!(input.axis("move_x"), 0.0);
+
+        key(&mut input, VirtualKeyCode::D, ElementState::Released);
+        assert_eq!(input.axis("move_x"), -1.0);
+    }
+
+    #[test]
+    fn analog_axes_are_scaled_per_frame() {
+        let mut input = Input::default();
+        input.set_actions(move_x());
+
+        input.handle_recorded_event(&RecordedEvent::MouseMotion { x: 4.0, y: 1.0 });
+        input.handle_recorded_event(&RecordedEvent::MouseWheel(MouseScrollDelta::LineDelta(
+            0.0, 2.0,
+        )));
+        assert_eq!(input.axis("look_x"), 2.0);
+        assert_eq!(input.axis("zoom"), -2.0);
+
+        input.end_frame();
+        assert_eq!(input.axis("look_x"), 0.0);
+        assert_eq!(input.axis("zoom"), 0.0);
+    }
+
+    #[test]
+    fn presses_only_last_a_frame() {
+        let mut input = Input::default();
+        input.set_actions(move_x());
+
+        key(&mut input, VirtualKeyCode::Space, ElementState::Pressed);
+        assert!(input.action_pressed("jump"));
+        assert!(input.action_held("jump"));
+        assert_eq!(
+            input.last_pressed(),
+            Some(Binding::Key(VirtualKeyCode::Space))
+        );
+
+        input.end_frame();
+        // a key repeat isn't a new press
+        key(&mut input, VirtualKeyCode::Space, ElementState::Pressed);
+        assert!(!input.action_pressed("jump"));
+        assert!(input.action_held("jump"));
+        assert_eq!(input.last_pressed(), None);
+
+        input.end_frame();
+        key(&mut input, VirtualKeyCode::Space, ElementState::Released);
+        assert!(input.action_released("jump"));
+        assert!(!input.action_held("jump"));
+
+        input.end_frame();
+        assert!(!input.action_released("jump"));
+    }
+
+    #[test]
+    fn losing_focus_releases_everything() {
+        let mut input = Input::default();
+        key(&mut input, VirtualKeyCode::D, ElementState::Pressed);
+        input.end_frame();
+
+        input.handle_recorded_event(&RecordedEvent::Focused(false));
+        assert!(!input.key_held(VirtualKeyCode::D));
+        assert!(input.key_released(VirtualKeyCode::D));
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let actions = move_x();
+
+        for ext in ["toml", "ron"] {
+            let path =
+                std::env::temp_dir().join(format!("engine-actions-{}.{ext}", std::process::id()));
+            actions.save(&path).unwrap();
+            let loaded = ActionMap::load(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            assert_eq!(loaded.actions, actions.actions);
+            assert_eq!(loaded.axes, actions.axes);
+        }
+
+        let missing = std::env::temp_dir().join("engine-actions-missing.toml");
+        let loaded = ActionMap::load_or(missing, actions.clone()).unwrap();
+        assert_eq!(loaded.actions, actions.actions);
+    }
+}
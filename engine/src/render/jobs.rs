@@ -0,0 +1,461 @@
+use std::{
+    collections::BinaryHeap,
+    fmt,
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use parking_lot::{Condvar, Mutex};
+
+/// Which queued jobs run first. Jobs of the same priority run in the order they were spawned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobError {
+    /// The job was cancelled before it ran, or the job system shut down first.
+    Cancelled,
+    Panicked,
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobError::Cancelled => write!(f, "the job was cancelled"),
+            JobError::Panicked => write!(f, "the job panicked"),
+        }
+    }
+}
+
+impl std::error::Error for JobError {}
+
+/// Handed to a running job, so long jobs can stop early once they're cancelled.
+#[derive(Debug, Clone)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+enum JobStatus<T> {
+    Pending,
+    Finished(Result<T, JobError>),
+    Taken,
+}
+
+struct JobState<T> {
+    status: Mutex<JobStatus<T>>,
+    finished: Condvar,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl<T> JobState<T> {
+    fn finish(&self, result: Result<T, JobError>) {
+        *self.status.lock() = JobStatus::Finished(result);
+        self.finished.notify_all();
+    }
+}
+
+/// The result of a job spawned on a [`JobSystem`].
+pub struct JobHandle<T> {
+    state: Arc<JobState<T>>,
+}
+
+impl<T> JobHandle<T> {
+    /// Keeps the job from running if it hasn't started yet. A running job keeps going unless it
+    /// checks its [`CancelToken`].
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Whether the result is ready to be taken.
+    pub fn is_finished(&self) -> bool {
+        matches!(*self.state.status.lock(), JobStatus::Finished(_))
+    }
+
+    /// Takes the result if the job has finished, only the first call after that returns it.
+    pub fn try_take(&mut self) -> Option<Result<T, JobError>> {
+        let mut status = self.state.status.lock();
+
+        match std::mem::replace(&mut *status, JobStatus::Taken) {
+            JobStatus::Finished(result) => Some(result),
+            pending => {
+                *status = pending;
+                None
+            }
+        }
+    }
+
+    /// Blocks until the job has finished. Fails with [`JobError::Cancelled`] if the result was
+    /// already taken.
+    pub fn wait(self) -> Result<T, JobError> {
+        let mut status = self.state.status.lock();
+
+        loop {
+            match std::mem::replace(&mut *status, JobStatus::Taken) {
+                JobStatus::Pending => {
+                    *status = JobStatus::Pending;
+                    self.state.finished.wait(&mut status);
+                }
+                JobStatus::Finished(result) => return result,
+                JobStatus::Taken => return Err(JobError::Cancelled),
+            }
+        }
+    }
+}
+
+struct QueuedJob {
+    priority: Priority,
+    sequence: u64,
+    // runs the job, or cancels it when passed false.
+    run: Box<dyn FnOnce(bool) + Send>,
+}
+
+impl PartialEq for QueuedJob {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for QueuedJob {}
+
+impl PartialOrd for QueuedJob {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedJob {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // the heap pops the greatest job, which should be the earliest one of the highest priority.
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+#[derive(Default)]
+struct Queue {
+    jobs: BinaryHeap<QueuedJob>,
+    next_sequence: u64,
+    shutdown: bool,
+}
+
+#[derive(Default)]
+struct Shared {
+    queue: Mutex<Queue>,
+    available: Condvar,
+}
+
+/// A thread pool for CPU heavy work, like generating meshes or decoding assets.
+///
+/// Threads are started along with the first job. If none of them can be started, jobs run on the
+/// thread spawning them instead. Dropping the job system waits for running jobs
+/// to finish, and cancels the queued ones.
+pub struct JobSystem {
+    shared: Arc<Shared>,
+    threads: usize,
+    workers: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl JobSystem {
+    pub fn new(threads: usize) -> Self {
+        Self {
+            shared: Arc::default(),
+            threads: threads.max(1),
+            workers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// A thread for every core but the one the main thread runs on.
+    pub fn with_available_parallelism() -> Self {
+        let cores = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        Self::new(cores.saturating_sub(1))
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Jobs that haven't started yet.
+    pub fn queued(&self) -> usize {
+        self.shared.queue.lock().jobs.len()
+    }
+
+    pub fn spawn<T, F>(&self, priority: Priority, job: F) -> JobHandle<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&CancelToken) -> T + Send + 'static,
+    {
+        let state = Arc::new(JobState {
+            status: Mutex::new(JobStatus::Pending),
+            finished: Condvar::new(),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        });
+
+        let job_state = state.clone();
+        let run = Box::new(move |run: bool| {
+            let token = CancelToken(job_state.cancelled.clone());
+
+            if !run || token.is_cancelled() {
+                job_state.finish(Err(JobError::Cancelled));
+                return;
+            }
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| job(&token)))
+                .map_err(|_| JobError::Panicked);
+            job_state.finish(result);
+        });
+
+        {
+            let mut queue = self.shared.queue.lock();
+            let sequence = queue.next_sequence;
+            queue.next_sequence += 1;
+
+            queue.jobs.push(QueuedJob {
+                priority,
+                sequence,
+                run,
+            });
+        }
+
+        match self.start_workers() {
+            true => {
+                self.shared.available.notify_one();
+            }
+            // nothing would ever take jobs off the queue, so they run on this thread instead.
+            false => run_queued(&self.shared),
+        }
+
+        JobHandle { state }
+    }
+
+    /// Starts the workers if they aren't running yet, returning whether any are.
+    fn start_workers(&self) -> bool {
+        let mut workers = self.workers.lock();
+
+        if !workers.is_empty() {
+            return true;
+        }
+
+        for idx in 0..self.threads {
+            let shared = self.shared.clone();
+            let worker = thread::Builder::new()
+                .name(format!("engine job worker {idx}"))
+                .spawn(move || work(&shared));
+
+            match worker {
+                Ok(worker) => workers.push(worker),
+                Err(err) => log::error!("unable to start a job worker: {err}"),
+            }
+        }
+
+        !workers.is_empty()
+    }
+}
+
+impl Default for JobSystem {
+    fn default() -> Self {
+        Self::with_available_parallelism()
+    }
+}
+
+impl Drop for JobSystem {
+    fn drop(&mut self) {
+        let cancelled = {
+            let mut queue = self.shared.queue.lock();
+            queue.shutdown = true;
+            std::mem::take(&mut queue.jobs)
+        };
+
+        self.shared.available.notify_all();
+
+        for job in cancelled {
+            (job.run)(false);
+        }
+
+        for worker in self.workers.get_mut().drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn work(shared: &Shared) {
+    loop {
+        let job = {
+            let mut queue = shared.queue.lock();
+
+            loop {
+                if queue.shutdown {
+                    return;
+                }
+
+                match queue.jobs.pop() {
+                    Some(job) => break job,
+                    None => shared.available.wait(&mut queue),
+                }
+            }
+        };
+
+        (job.run)(true);
+    }
+}
+
+fn run_queued(shared: &Shared) {
+    loop {
+        let Some(job) = shared.queue.lock().jobs.pop() else {
+            return;
+        };
+
+        (job.run)(true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+
+    /// Keeps the only worker of `jobs` busy until the returned sender is used.
+    fn block(jobs: &JobSystem) -> mpsc::Sender<()> {
+        let (started, has_started) = mpsc::channel();
+        let (release, released) = mpsc::channel::<()>();
+
+        jobs.spawn(Priority::High, move |_| {
+            started.send(()).unwrap();
+            let _ = released.recv();
+        });
+        has_started.recv().unwrap();
+
+        release
+    }
+
+    #[test]
+    fn runs_higher_priorities_first() {
+        let jobs = JobSystem::new(1);
+        let release = block(&jobs);
+        let ran = Arc::new(Mutex::new(Vec::new()));
+
+        let handles = [
+            (Priority::Low, "low"),
+            (Priority::Normal, "normal"),
+            (Priority::High, "high"),
+            (Priority::Normal, "normal again"),
+            (Priority::High, "high again"),
+        ]
+        .map(|(priority, name)| {
+            let ran = ran.clone();
+            jobs.spawn(priority, move |_| ran.lock().push(name))
+        });
+
+        assert_eq!(jobs.queued(), 5);
+        release.send(()).unwrap();
+
+        for handle in handles {
+            handle.wait().unwrap();
+        }
+
+        assert_eq!(
+            *ran.lock(),
+            ["high", "high again", "normal", "normal again", "low"]
+        );
+    }
+
+    #[test]
+    fn cancelled_jobs_dont_run() {
+        let jobs = JobSystem::new(1);
+        let release = block(&jobs);
+        let ran = Arc::new(AtomicBool::new(false));
+
+        let job = {
+            let ran = ran.clone();
+            jobs.spawn(Priority::Normal, move |_| {
+                ran.store(true, Ordering::Relaxed)
+            })
+        };
+        job.cancel();
+        release.send(()).unwrap();
+
+        assert!(job.is_cancelled());
+        assert_eq!(job.wait(), Err(JobError::Cancelled));
+        assert!(!ran.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn running_jobs_see_their_cancellation() {
+        let jobs = JobSystem::new(1);
+        let (started, has_started) = mpsc::channel();
+
+        let job = jobs.spawn(Priority::Normal, move |token| {
+            started.send(()).unwrap();
+
+            while !token.is_cancelled() {
+                thread::yield_now();
+            }
+
+            "stopped early"
+        });
+
+        has_started.recv().unwrap();
+        job.cancel();
+        assert_eq!(job.wait(), Ok("stopped early"));
+    }
+
+    #[test]
+    fn panics_are_caught() {
+        let jobs = JobSystem::new(1);
+        let job = jobs.spawn(Priority::Normal, |_| -> u32 { panic!("on purpose") });
+
+        assert_eq!(job.wait(), Err(JobError::Panicked));
+        // the worker survives it.
+        assert_eq!(jobs.spawn(Priority::Normal, |_| 7).wait(), Ok(7));
+    }
+
+    #[test]
+    fn results_are_taken_once() {
+        let jobs = JobSystem::new(1);
+        let mut job = jobs.spawn(Priority::Normal, |_| 1);
+
+        while !job.is_finished() {
+            thread::yield_now();
+        }
+
+        assert_eq!(job.try_take(), Some(Ok(1)));
+        assert_eq!(job.try_take(), None);
+        assert_eq!(job.wait(), Err(JobError::Cancelled));
+    }
+
+    #[test]
+    fn dropping_cancels_queued_jobs() {
+        let jobs = JobSystem::new(1);
+        let release = block(&jobs);
+        let job = jobs.spawn(Priority::Low, |_| ());
+
+        // dropping waits for the blocked worker, so it's released from another thread.
+        let releaser = thread::spawn(move || {
+            thread::sleep(std::time::Duration::from_millis(50));
+            release.send(()).unwrap();
+        });
+        drop(jobs);
+        releaser.join().unwrap();
+
+        assert_eq!(job.wait(), Err(JobError::Cancelled));
+    }
+}
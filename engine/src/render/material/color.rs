@@ -33,7 +33,11 @@ impl IntoRawBinder for StaticColorMaterial {
         let device = params.device;
         let config = params.config;
 
-        let color_tab: [f32; 4] = self.color.into();
+        // the shader outputs the color as is, so it's converted for the surface up front.
+        let color_tab = self
+            .color
+            .to_format(config.format)
+            .map(|value| value as f32);
         let color_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("voxel_chunk_vertices"),
             contents: bytemuck::cast_slice(&color_tab),
@@ -62,6 +66,8 @@ impl IntoRawBinder for StaticColorMaterial {
             .shader_module(shader)
             .label("tri")
             .fragment(true)
+            .depth_format(params.targets.depth_format())
+            .sample_count(params.targets.sample_count())
             .build()
             .into_pipeline(device, config);
 
@@ -79,7 +85,7 @@ impl RawStaticColorMaterial {
         buffer: &wgpu::Buffer,
     ) -> BindGroup {
         device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &layout,
+            layout,
             entries: &[wgpu::BindGroupEntry {
                 binding: 0,
                 resource: buffer.as_entire_binding(),
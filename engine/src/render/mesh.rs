@@ -1,9 +1,10 @@
+use glam::{Mat4, Vec3};
 use typed_builder::TypedBuilder;
 use wgpu::{util::DeviceExt, RenderPass};
 
-use super::vertex::{TransformRaw, Vertex};
+use super::vertex::{Transform, TransformRaw, Vertex};
 
-#[derive(TypedBuilder, Debug)]
+#[derive(TypedBuilder, Debug, Clone)]
 pub struct Mesh {
     pub vertices: Vec<Vertex>,
     #[builder(default, setter(strip_option))]
@@ -54,6 +55,96 @@ impl Mesh {
             num_indices: index_count,
         }
     }
+
+    /// The indices of every triangle, made up for meshes without indices.
+    pub fn triangle_indices(&self) -> Vec<u32> {
+        match &self.indices {
+            Some(indices) => indices.clone(),
+            None => (0..self.vertices.len() as u32).collect(),
+        }
+    }
+
+    /// A copy of the mesh with its vertices moved by `transform`.
+    pub fn transformed(&self, transform: &Transform) -> Self {
+        self.transformed_by(transform.matrix())
+    }
+
+    /// A copy of the mesh with its vertices moved by `model`, and its normals turned with them.
+    pub fn transformed_by(&self, model: Mat4) -> Self {
+        let normal_matrix = model.inverse().transpose();
+
+        let vertices = self
+            .vertices
+            .iter()
+            .map(|vertex| Vertex {
+                position: model.transform_point3(vertex.position.into()).to_array(),
+                normal: normal_matrix
+                    .transform_vector3(vertex.normal.into())
+                    .normalize_or_zero()
+                    .to_array(),
+            })
+            .collect();
+
+        Self {
+            vertices,
+            indices: self.indices.clone(),
+        }
+    }
+
+    /// Combines meshes into a single indexed one.
+    pub fn merge(meshes: impl IntoIterator<Item = Mesh>) -> Self {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        for mesh in meshes {
+            let offset = vertices.len() as u32;
+
+            indices.extend(mesh.triangle_indices().into_iter().map(|idx| idx + offset));
+            vertices.extend(mesh.vertices);
+        }
+
+        Self {
+            vertices,
+            indices: Some(indices),
+        }
+    }
+
+    /// Replaces the normals with smooth ones, averaged from the triangles around each vertex and
+    /// weighted by their area.
+    pub fn generate_normals(&mut self) {
+        let mut normals = vec![Vec3::ZERO; self.vertices.len()];
+        let triangles = self.triangle_indices();
+
+        for triangle in triangles.chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]].map(|idx| idx as usize);
+
+            if [a, b, c].iter().any(|idx| *idx >= self.vertices.len()) {
+                continue;
+            }
+
+            let [pa, pb, pc] = [a, b, c].map(|idx| Vec3::from(self.vertices[idx].position));
+            let normal = (pb - pa).cross(pc - pa);
+
+            for idx in [a, b, c] {
+                normals[idx] += normal;
+            }
+        }
+
+        for (vertex, normal) in self.vertices.iter_mut().zip(normals) {
+            vertex.normal = normal.normalize_or_zero().to_array();
+        }
+    }
+
+    /// Reverses the winding of every triangle, e.g. for a mesh placed with a mirroring transform.
+    pub fn flip_winding(&mut self) {
+        let mut indices = self.triangle_indices();
+
+        for triangle in indices.chunks_exact_mut(3) {
+            triangle.swap(1, 2);
+        }
+
+        self.indices = Some(indices);
+    }
 }
 
 #[derive(Debug)]
@@ -74,6 +165,7 @@ pub trait UntypedMeshRender<'a> {
         num_vertices: usize,
     );
 
+    #[allow(clippy::too_many_arguments, clippy::ptr_arg)]
     fn render_instanced_raw_mesh(
         &mut self,
         idx: u32,
@@ -88,6 +180,7 @@ pub trait UntypedMeshRender<'a> {
 
 pub trait MeshRender<'a> {
     fn render_single_mesh(&mut self, idx: u32, mesh: &'a RawMesh);
+    #[allow(clippy::ptr_arg)]
     fn render_instanced_mesh(
         &mut self,
         idx: u32,
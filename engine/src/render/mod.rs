@@ -1,16 +1,39 @@
 use generational_arena::Arena;
 
+pub mod app;
 pub mod builder;
 pub mod bundle;
 pub mod camera;
+pub mod capture;
 pub mod color;
+pub mod config;
+pub mod context;
+pub mod error;
+pub mod export;
 pub mod framework;
+pub mod gltf;
+pub mod golden;
+pub mod gradient;
+pub mod graph;
 pub mod handle;
+pub mod headless;
+pub mod input;
+pub mod jobs;
 pub mod material;
 pub mod mesh;
+pub mod obj;
+pub mod pacing;
+pub mod plugin;
+pub mod primitive;
+pub mod profiler;
 pub mod raw;
+pub mod replay;
+pub mod stats;
+pub mod target;
 pub mod time;
+pub mod upload;
 pub mod vertex;
+pub mod window;
 
 pub const ARENA_CAPACITY: usize = 32;
 
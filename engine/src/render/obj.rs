@@ -0,0 +1,790 @@
+use std::{collections::HashMap, fmt, path::Path, str::SplitWhitespace};
+
+use anyhow::Context as _;
+use glam::Vec3;
+
+use super::{
+    bundle::mesh::MeshBundle, color::Color, material::color::StaticColorMaterial, mesh::Mesh,
+    vertex::Vertex,
+};
+
+/// The color of meshes without a material, or with one that none of the MTL files define.
+pub const DEFAULT_COLOR: Color = Color {
+    r: 0.8,
+    g: 0.8,
+    b: 0.8,
+    a: 1.0,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ObjErrorKind {
+    /// The statement is missing some of its values.
+    MissingValues(String),
+    InvalidNumber(String),
+    /// A face vertex that isn't `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+    InvalidFaceVertex(String),
+    /// A face refers to a position, texture coordinate or normal that isn't defined before it.
+    IndexOutOfRange {
+        kind: &'static str,
+        index: i64,
+        count: usize,
+    },
+    TooFewVertices(usize),
+    /// A material property came before any `newmtl`.
+    NoMaterial(String),
+}
+
+/// Malformed OBJ or MTL input, and the line it's on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjError {
+    pub line: usize,
+    pub kind: ObjErrorKind,
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+
+        match &self.kind {
+            ObjErrorKind::MissingValues(statement) => {
+                write!(f, "`{statement}` is missing values")
+            }
+            ObjErrorKind::InvalidNumber(value) => write!(f, "{value:?} isn't a number"),
+            ObjErrorKind::InvalidFaceVertex(value) => {
+                write!(f, "{value:?} isn't a face vertex")
+            }
+            ObjErrorKind::IndexOutOfRange { kind, index, count } => {
+                write!(f, "{kind} {index} doesn't exist, there are {count}")
+            }
+            ObjErrorKind::TooFewVertices(count) => {
+                write!(f, "a face needs at least 3 vertices, this one has {count}")
+            }
+            ObjErrorKind::NoMaterial(statement) => {
+                write!(f, "`{statement}` comes before any `newmtl`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+/// A material from an MTL file. Only the diffuse color and its opacity are used.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjMaterial {
+    pub name: String,
+    pub diffuse: Color,
+}
+
+impl ObjMaterial {
+    /// Parses the materials of an MTL file.
+    pub fn parse_library(source: &str) -> Result<Vec<ObjMaterial>, ObjError> {
+        let mut materials: Vec<ObjMaterial> = Vec::new();
+
+        for (idx, line) in source.lines().enumerate() {
+            let line_number = idx + 1;
+            let err = |kind| ObjError {
+                line: line_number,
+                kind,
+            };
+
+            let mut args = statement(line);
+            let Some(keyword) = args.next() else {
+                continue;
+            };
+
+            if keyword == "newmtl" {
+                let name = rest(args).ok_or_else(|| err(missing(keyword)))?;
+
+                materials.push(ObjMaterial {
+                    name,
+                    diffuse: DEFAULT_COLOR,
+                });
+                continue;
+            }
+
+            if !matches!(keyword, "Kd" | "d" | "Tr") {
+                continue;
+            }
+
+            let material = materials
+                .last_mut()
+                .ok_or_else(|| err(ObjErrorKind::NoMaterial(keyword.to_string())))?;
+
+            match keyword {
+                "Kd" => {
+                    let [r, g, b] = floats(keyword, &mut args, 3).map_err(err)?;
+                    material.diffuse = Color::srgb(r, g, b, material.diffuse.a);
+                }
+                "d" => {
+                    let [alpha] = floats(keyword, &mut args, 1).map_err(err)?;
+                    material.diffuse.a = alpha;
+                }
+                _ => {
+                    let [transparency] = floats(keyword, &mut args, 1).map_err(err)?;
+                    material.diffuse.a = 1.0 - transparency;
+                }
+            }
+        }
+
+        Ok(materials)
+    }
+
+    pub fn to_material(&self) -> StaticColorMaterial {
+        StaticColorMaterial::builder().color(self.diffuse).build()
+    }
+}
+
+/// A part of an OBJ file drawn with a single material.
+#[derive(Debug)]
+pub struct ObjMesh {
+    /// The group or object the faces belong to, if any.
+    pub name: Option<String>,
+    pub material: Option<String>,
+    pub mesh: Mesh,
+}
+
+/// The meshes of a Wavefront OBJ file, and the materials its MTL files define.
+///
+/// Faces are split into a mesh per group or object and material, with polygons triangulated as
+/// fans. Faces without normals get smooth normals averaged from the faces around each position.
+/// Texture coordinates are checked but dropped, as [`Vertex`] has nowhere to keep them.
+#[derive(Debug, Default)]
+pub struct ObjModel {
+    pub meshes: Vec<ObjMesh>,
+    pub materials: HashMap<String, ObjMaterial>,
+    /// The MTL files the OBJ file refers to, as written in it.
+    pub material_libraries: Vec<String>,
+}
+
+impl ObjModel {
+    /// Loads an OBJ file, and the MTL files it refers to from next to it. A missing MTL file
+    /// is logged and its materials fall back to [`DEFAULT_COLOR`].
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let source =
+            std::fs::read_to_string(path).with_context(|| format!("unable to read {path:?}"))?;
+        let mut model =
+            Self::parse(&source).with_context(|| format!("unable to parse {path:?}"))?;
+
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+        for library in &model.material_libraries {
+            let library = dir.join(library);
+
+            let source = match std::fs::read_to_string(&library) {
+                Ok(source) => source,
+                Err(err) => {
+                    log::warn!("unable to read material library {library:?}: {err}");
+                    continue;
+                }
+            };
+
+            let materials = ObjMaterial::parse_library(&source)
+                .with_context(|| format!("unable to parse {library:?}"))?;
+
+            for material in materials {
+                model.materials.insert(material.name.clone(), material);
+            }
+        }
+
+        Ok(model)
+    }
+
+    /// Parses an OBJ file, without loading the MTL files it refers to.
+    pub fn parse(source: &str) -> Result<Self, ObjError> {
+        let mut parser = Parser::default();
+
+        for (idx, line) in source.lines().enumerate() {
+            parser.line = idx + 1;
+            parser.parse_line(line)?;
+        }
+
+        parser.flush();
+
+        Ok(Self {
+            meshes: parser.meshes,
+            materials: HashMap::new(),
+            material_libraries: parser.material_libraries,
+        })
+    }
+
+    /// The material `mesh` is drawn with, or [`DEFAULT_COLOR`] if it has none.
+    pub fn material(&self, mesh: &ObjMesh) -> StaticColorMaterial {
+        mesh.material
+            .as_ref()
+            .and_then(|name| self.materials.get(name))
+            .map(ObjMaterial::to_material)
+            .unwrap_or_else(|| StaticColorMaterial::builder().color(DEFAULT_COLOR).build())
+    }
+
+    /// A bundle for every mesh, ready for [`Bundles::add`](super::bundle::mesh::Bundles::add).
+    pub fn into_bundles(mut self) -> Vec<MeshBundle<StaticColorMaterial>> {
+        std::mem::take(&mut self.meshes)
+            .into_iter()
+            .map(|mesh| {
+                MeshBundle::builder()
+                    .material(self.material(&mesh))
+                    .mesh(mesh.mesh)
+                    .build()
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Corner {
+    position: usize,
+    normal: Option<usize>,
+}
+
+#[derive(Default)]
+struct Parser {
+    line: usize,
+    positions: Vec<Vec3>,
+    normals: Vec<Vec3>,
+    texcoords: usize,
+    object: Option<String>,
+    group: Option<String>,
+    material: Option<String>,
+    triangles: Vec<[Corner; 3]>,
+    meshes: Vec<ObjMesh>,
+    material_libraries: Vec<String>,
+}
+
+impl Parser {
+    fn err(&self, kind: ObjErrorKind) -> ObjError {
+        ObjError {
+            line: self.line,
+            kind,
+        }
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), ObjError> {
+        let mut args = statement(line);
+        let Some(keyword) = args.next() else {
+            return Ok(());
+        };
+
+        match keyword {
+            "v" => {
+                let position = floats(keyword, &mut args, 3).map_err(|kind| self.err(kind))?;
+                self.positions
+                    .push(Vec3::from(position.map(|value| value as f32)));
+            }
+            "vn" => {
+                let normal = floats(keyword, &mut args, 3).map_err(|kind| self.err(kind))?;
+                self.normals
+                    .push(Vec3::from(normal.map(|value| value as f32)));
+            }
+            "vt" => {
+                floats::<1>(keyword, &mut args, 1).map_err(|kind| self.err(kind))?;
+                self.texcoords += 1;
+            }
+            "f" => {
+                let corners = args
+                    .map(|vertex| self.corner(vertex))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if corners.len() < 3 {
+                    return Err(self.err(ObjErrorKind::TooFewVertices(corners.len())));
+                }
+
+                for idx in 1..corners.len() - 1 {
+                    self.triangles
+                        .push([corners[0], corners[idx], corners[idx + 1]]);
+                }
+            }
+            "o" | "usemtl" => {
+                let name = rest(args).ok_or_else(|| self.err(missing(keyword)))?;
+                self.flush();
+
+                match keyword {
+                    "o" => {
+                        self.object = Some(name);
+                        self.group = None;
+                    }
+                    _ => self.material = Some(name),
+                }
+            }
+            // a group without a name goes back to the default group.
+            "g" => {
+                self.flush();
+                self.group = rest(args);
+            }
+            "mtllib" => {
+                let libraries = args.map(str::to_string).collect::<Vec<_>>();
+
+                if libraries.is_empty() {
+                    return Err(self.err(missing(keyword)));
+                }
+
+                self.material_libraries.extend(libraries);
+            }
+            // smoothing groups, lines, points and curves.
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn corner(&self, vertex: &str) -> Result<Corner, ObjError> {
+        let invalid = || self.err(ObjErrorKind::InvalidFaceVertex(vertex.to_string()));
+        let mut parts = vertex.split('/');
+
+        let position = parts
+            .next()
+            .filter(|part| !part.is_empty())
+            .ok_or_else(invalid)?;
+        let texcoord = parts.next().filter(|part| !part.is_empty());
+        let normal = parts.next().filter(|part| !part.is_empty());
+
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+
+        let position = self.index(position, "position", self.positions.len())?;
+
+        if let Some(texcoord) = texcoord {
+            self.index(texcoord, "texture coordinate", self.texcoords)?;
+        }
+
+        let normal = normal
+            .map(|normal| self.index(normal, "normal", self.normals.len()))
+            .transpose()?;
+
+        Ok(Corner { position, normal })
+    }
+
+    /// Resolves a one-based index, or a negative one counting back from the last element.
+    fn index(&self, value: &str, kind: &'static str, count: usize) -> Result<usize, ObjError> {
+        let index = value
+            .parse::<i64>()
+            .map_err(|_| self.err(ObjErrorKind::InvalidNumber(value.to_string())))?;
+
+        let resolved = match index {
+            index if index > 0 => index - 1,
+            index => count as i64 + index,
+        };
+
+        match usize::try_from(resolved) {
+            Ok(resolved) if index != 0 && resolved < count => Ok(resolved),
+            _ => Err(self.err(ObjErrorKind::IndexOutOfRange { kind, index, count })),
+        }
+    }
+
+    /// Turns the faces so far into a mesh.
+    fn flush(&mut self) {
+        if self.triangles.is_empty() {
+            return;
+        }
+
+        let triangles = std::mem::take(&mut self.triangles);
+
+        // faces without normals share the sum of their normals at every position they touch,
+        // weighted by their area.
+        let mut generated: HashMap<usize, Vec3> = HashMap::new();
+
+        for triangle in &triangles {
+            if triangle.iter().all(|corner| corner.normal.is_some()) {
+                continue;
+            }
+
+            let [a, b, c] = triangle.map(|corner| self.positions[corner.position]);
+            let normal = (b - a).cross(c - a);
+
+            for corner in triangle.iter().filter(|corner| corner.normal.is_none()) {
+                *generated.entry(corner.position).or_default() += normal;
+            }
+        }
+
+        let mut vertices = Vec::new();
+        let mut lookup: HashMap<(usize, Option<usize>), u32> = HashMap::new();
+
+        let indices = triangles
+            .iter()
+            .flatten()
+            .map(|corner| {
+                *lookup
+                    .entry((corner.position, corner.normal))
+                    .or_insert_with(|| {
+                        let normal = match corner.normal {
+                            Some(normal) => self.normals[normal],
+                            None => generated[&corner.position],
+                        };
+
+                        vertices.push(
+                            Vertex::builder()
+                                .position(self.positions[corner.position].to_array())
+                                .normal(normal.normalize_or_zero().to_array())
+                                .build(),
+                        );
+                        vertices.len() as u32 - 1
+                    })
+            })
+            .collect();
+
+        self.meshes.push(ObjMesh {
+            name: self.group.clone().or_else(|| self.object.clone()),
+            material: self.material.clone(),
+            mesh: Mesh::builder().vertices(vertices).indices(indices).build(),
+        });
+    }
+}
+
+/// The words of a line, without its comment.
+fn statement(line: &str) -> SplitWhitespace<'_> {
+    line.split('#')
+        .next()
+        .unwrap_or_default()
+        .split_whitespace()
+}
+
+/// The remaining words, as names may contain spaces.
+fn rest(args: SplitWhitespace<'_>) -> Option<String> {
+    let rest = args.collect::<Vec<_>>().join(" ");
+    (!rest.is_empty()).then_some(rest)
+}
+
+fn missing(keyword: &str) -> ObjErrorKind {
+    ObjErrorKind::MissingValues(keyword.to_string())
+}
+
+/// Parses the next `N` numbers, of which the first `required` have to be there.
+fn floats<const N: usize>(
+    keyword: &str,
+    args: &mut SplitWhitespace<'_>,
+    required: usize,
+) -> Result<[f64; N], ObjErrorKind> {
+    let mut values = [0.0; N];
+
+    for (idx, value) in values.iter_mut().enumerate() {
+        match args.next() {
+            Some(arg) => {
+                *value = arg
+                    .parse()
+                    .map_err(|_| ObjErrorKind::InvalidNumber(arg.to_string()))?;
+            }
+            None if idx < required => return Err(missing(keyword)),
+            None => break,
+        }
+    }
+
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUAD: &str = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n";
+
+    fn parse(source: &str) -> ObjModel {
+        ObjModel::parse(source).unwrap()
+    }
+
+    fn positions(mesh: &ObjMesh) -> Vec<[f32; 3]> {
+        mesh.mesh
+            .vertices
+            .iter()
+            .map(|vertex| vertex.position)
+            .collect()
+    }
+
+    fn normals(mesh: &ObjMesh) -> Vec<[f32; 3]> {
+        mesh.mesh
+            .vertices
+            .iter()
+            .map(|vertex| vertex.normal)
+            .collect()
+    }
+
+    fn assert_close(actual: [f32; 3], expected: [f32; 3]) {
+        assert!(
+            Vec3::from(actual).abs_diff_eq(Vec3::from(expected), 1e-6),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn triangulates_polygons_as_fans() {
+        let model = parse(&format!("{QUAD}f 1 2 3 4\n"));
+        let [mesh] = &model.meshes[..] else {
+            panic!("expected one mesh, got {:?}", model.meshes);
+        };
+
+        assert_eq!(
+            positions(mesh),
+            [
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0]
+            ]
+        );
+        assert_eq!(mesh.mesh.indices, Some(vec![0, 1, 2, 0, 2, 3]));
+
+        let model = parse(&format!("{QUAD}v 0.5 1.5 0\nf 1 2 3 5 4\n"));
+        assert_eq!(
+            model.meshes[0].mesh.indices,
+            Some(vec![0, 1, 2, 0, 2, 3, 0, 3, 4])
+        );
+    }
+
+    #[test]
+    fn resolves_negative_indices() {
+        let relative = parse(&format!(
+            "{QUAD}vn 0 0 1\nvn 0 0 -1\nf -4//-2 -3//-2 -2//-2 -1//-2\n"
+        ));
+        let absolute = parse(&format!(
+            "{QUAD}vn 0 0 1\nvn 0 0 -1\nf 1//1 2//1 3//1 4//1\n"
+        ));
+
+        assert_eq!(
+            positions(&relative.meshes[0]),
+            positions(&absolute.meshes[0])
+        );
+        assert_eq!(
+            relative.meshes[0].mesh.indices,
+            absolute.meshes[0].mesh.indices
+        );
+        assert_eq!(normals(&relative.meshes[0]), [[0.0, 0.0, 1.0]; 4]);
+
+        // negative indices count back from the last element so far, not from the end of the file.
+        let model = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nf -3 -2 -1\nv 5 5 5\nf -4 -3 -2\n");
+        assert_eq!(
+            positions(&model.meshes[0]),
+            [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+        assert_eq!(model.meshes[0].mesh.indices, Some(vec![0, 1, 2, 0, 1, 2]));
+    }
+
+    #[test]
+    fn shares_vertices_with_the_same_position_and_normal() {
+        let model = parse(&format!(
+            "{QUAD}vn 0 0 1\nvn 1 0 0\nf 1//1 2//1 3//1\nf 1//1 3//1 4//1\n"
+        ));
+        assert_eq!(model.meshes[0].mesh.vertices.len(), 4);
+        assert_eq!(model.meshes[0].mesh.indices, Some(vec![0, 1, 2, 0, 2, 3]));
+
+        // the same positions with another normal are other vertices.
+        let model = parse(&format!(
+            "{QUAD}vn 0 0 1\nvn 1 0 0\nf 1//1 2//1 3//1\nf 1//2 3//2 4//2\n"
+        ));
+        assert_eq!(model.meshes[0].mesh.vertices.len(), 6);
+        assert_eq!(model.meshes[0].mesh.indices, Some(vec![0, 1, 2, 3, 4, 5]));
+        assert_eq!(
+            normals(&model.meshes[0]),
+            [
+                [0.0, 0.0, 1.0],
+                [0.0, 0.0, 1.0],
+                [0.0, 0.0, 1.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+            ]
+        );
+
+        // texture coordinates are dropped, so they don't split vertices.
+        let model = parse(&format!(
+            "{QUAD}vt 0 0\nvt 1 1\nvn 0 0 1\nf 1/1/1 2/1/1 3/1/1\nf 1/2/1 3/2/1 4/2/1\n"
+        ));
+        assert_eq!(model.meshes[0].mesh.vertices.len(), 4);
+    }
+
+    #[test]
+    fn generates_smooth_normals() {
+        let model = parse(&format!("{QUAD}f 1 2 3 4\n"));
+        assert_eq!(normals(&model.meshes[0]), [[0.0, 0.0, 1.0]; 4]);
+
+        // the two faces meet at a right angle along the edge from 1 to 2.
+        let model = parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nf 1 2 3\nf 1 4 2\n");
+        let bent = normals(&model.meshes[0]);
+        let diagonal = std::f32::consts::FRAC_1_SQRT_2;
+
+        assert_eq!(bent.len(), 4);
+        assert_close(bent[0], [0.0, diagonal, diagonal]);
+        assert_close(bent[1], [0.0, diagonal, diagonal]);
+        assert_close(bent[2], [0.0, 0.0, 1.0]);
+        assert_close(bent[3], [0.0, 1.0, 0.0]);
+
+        // faces with their own normals don't bend the generated ones.
+        let model =
+            parse("v 0 0 0\nv 1 0 0\nv 0 1 0\nv 0 0 1\nvn 1 0 0\nf 1 2 3\nf 1//1 4//1 2//1\n");
+        let mixed = normals(&model.meshes[0]);
+
+        assert_eq!(mixed.len(), 6);
+        assert_close(mixed[0], [0.0, 0.0, 1.0]);
+        assert_close(mixed[1], [0.0, 0.0, 1.0]);
+        assert_close(mixed[3], [1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn splits_meshes_by_material_group_and_object() {
+        let model = parse(
+            "mtllib a.mtl b.mtl\n\
+             v 0 0 0\nv 1 0 0\nv 0 1 0\n\
+             f 1 2 3\n\
+             o empty\n\
+             o cube\n\
+             usemtl red\n\
+             f 1 2 3\n\
+             g side one\n\
+             f 1 2 3\n\
+             f 1 2 3\n\
+             usemtl blue\n\
+             f 1 2 3\n\
+             g\n\
+             f 1 2 3\n\
+             o other\n\
+             f 1 2 3\n",
+        );
+
+        let parts = model
+            .meshes
+            .iter()
+            .map(|mesh| {
+                (
+                    mesh.name.as_deref(),
+                    mesh.material.as_deref(),
+                    mesh.mesh.indices.as_ref().map_or(0, Vec::len) / 3,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            parts,
+            [
+                (None, None, 1),
+                (Some("cube"), Some("red"), 1),
+                (Some("side one"), Some("red"), 2),
+                (Some("side one"), Some("blue"), 1),
+                (Some("cube"), Some("blue"), 1),
+                (Some("other"), Some("blue"), 1),
+            ]
+        );
+        assert_eq!(model.material_libraries, ["a.mtl", "b.mtl"]);
+    }
+
+    #[test]
+    fn parses_material_libraries() {
+        let materials = ObjMaterial::parse_library(
+            "# exported materials\n\
+             newmtl red\n\
+             Kd 1 0 0\n\
+             d 0.5\n\
+             Ns 10 # ignored\n\
+             newmtl glass pane\n\
+             Kd 0.5 0.5 0.5\n\
+             Tr 0.75\n\
+             newmtl late\n\
+             d 0.5\n\
+             Kd 0 1 0\n\
+             newmtl plain\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            materials,
+            [
+                ObjMaterial {
+                    name: "red".to_string(),
+                    diffuse: Color::srgb(1.0, 0.0, 0.0, 0.5),
+                },
+                ObjMaterial {
+                    name: "glass pane".to_string(),
+                    diffuse: Color::srgb(0.5, 0.5, 0.5, 0.25),
+                },
+                ObjMaterial {
+                    name: "late".to_string(),
+                    diffuse: Color::srgb(0.0, 1.0, 0.0, 0.5),
+                },
+                ObjMaterial {
+                    name: "plain".to_string(),
+                    diffuse: DEFAULT_COLOR,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_the_line_of_each_error() {
+        let triangle = "v 0 0 0\nv 1 0 0\n\n# comment\nv 0 1 0\n";
+        let error = |source: &str| ObjModel::parse(&format!("{triangle}{source}")).unwrap_err();
+        let out_of_range =
+            |kind, index, count| ObjErrorKind::IndexOutOfRange { kind, index, count };
+
+        for (source, line, kind) in [
+            ("v 1 2\n", 6, ObjErrorKind::MissingValues("v".to_string())),
+            (
+                "f 1 2 3\nusemtl\n",
+                7,
+                ObjErrorKind::MissingValues("usemtl".to_string()),
+            ),
+            (
+                "mtllib # none\n",
+                6,
+                ObjErrorKind::MissingValues("mtllib".to_string()),
+            ),
+            (
+                "vn 0 x 1\n",
+                6,
+                ObjErrorKind::InvalidNumber("x".to_string()),
+            ),
+            ("f 1 a 3\n", 6, ObjErrorKind::InvalidNumber("a".to_string())),
+            (
+                "f 1/2/3/4 2 3\n",
+                6,
+                ObjErrorKind::InvalidFaceVertex("1/2/3/4".to_string()),
+            ),
+            (
+                "f 1 /2 3\n",
+                6,
+                ObjErrorKind::InvalidFaceVertex("/2".to_string()),
+            ),
+            ("f 1 2 4\n", 6, out_of_range("position", 4, 3)),
+            ("f 0 1 2\n", 6, out_of_range("position", 0, 3)),
+            ("f -4 1 2\n", 6, out_of_range("position", -4, 3)),
+            ("f 1/1 2 3\n", 6, out_of_range("texture coordinate", 1, 0)),
+            (
+                "vn 0 0 1\nf 1//2 2//1 3//1\n",
+                7,
+                out_of_range("normal", 2, 1),
+            ),
+            ("f 1 2\n", 6, ObjErrorKind::TooFewVertices(2)),
+        ] {
+            assert_eq!(error(source), ObjError { line, kind }, "{source:?}");
+        }
+
+        for (source, line, kind) in [
+            (
+                "newmtl a\nKd 1 1\n",
+                2,
+                ObjErrorKind::MissingValues("Kd".to_string()),
+            ),
+            (
+                "\nnewmtl\n",
+                2,
+                ObjErrorKind::MissingValues("newmtl".to_string()),
+            ),
+            (
+                "newmtl a\nd half\n",
+                2,
+                ObjErrorKind::InvalidNumber("half".to_string()),
+            ),
+            (
+                "# colors\nKd 1 1 1\n",
+                2,
+                ObjErrorKind::NoMaterial("Kd".to_string()),
+            ),
+            ("Tr 0.5\n", 1, ObjErrorKind::NoMaterial("Tr".to_string())),
+        ] {
+            let error = ObjMaterial::parse_library(source).unwrap_err();
+            assert_eq!(error, ObjError { line, kind }, "{source:?}");
+        }
+
+        assert_eq!(
+            error("f 1 2 4\n").to_string(),
+            "line 6: position 4 doesn't exist, there are 3"
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use std::time::{Duration, Instant};
+
+use winit::event_loop::ControlFlow;
+
+/// How long before a frame is due the event loop stops sleeping and spins instead, as waking up
+/// from a sleep can be late by a millisecond or more on some platforms.
+pub const SPIN_MARGIN: Duration = Duration::from_millis(2);
+
+/// Decides when frames start under a [`FramerateLimit`](super::framework::FramerateLimit).
+///
+/// Frames are scheduled on a fixed cadence rather than relative to when the last one finished,
+/// so the framerate doesn't drift. A frame that overruns its slot starts a new cadence instead of
+/// trying to catch up with a burst of frames.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FramePacer {
+    deadline: Option<Instant>,
+    last_start: Option<Instant>,
+}
+
+impl FramePacer {
+    /// Sleeps until shortly before the next frame is due, then polls so the rest can be spun.
+    pub fn control_flow(&self) -> ControlFlow {
+        self.control_flow_at(Instant::now())
+    }
+
+    fn control_flow_at(&self, now: Instant) -> ControlFlow {
+        match self.deadline {
+            Some(deadline) if too_early(now, deadline) => {
+                ControlFlow::WaitUntil(deadline - SPIN_MARGIN)
+            }
+            _ => ControlFlow::Poll,
+        }
+    }
+
+    /// Spins until the next frame is due and returns true, or returns false right away if the
+    /// event loop woke up too early to be worth spinning.
+    pub fn wait(&mut self, frame_time: Option<Duration>) -> bool {
+        self.limit(frame_time);
+
+        let Some(deadline) = self.deadline else {
+            return true;
+        };
+
+        if too_early(Instant::now(), deadline) {
+            return false;
+        }
+
+        while Instant::now() < deadline {
+            std::hint::spin_loop();
+        }
+
+        true
+    }
+
+    /// Brings the next frame forward if the limit was raised since it was scheduled, e.g. on
+    /// regaining focus, or drops it if there is no limit anymore.
+    fn limit(&mut self, frame_time: Option<Duration>) {
+        self.deadline = match (self.deadline, self.last_start, frame_time) {
+            (Some(deadline), Some(last_start), Some(frame_time)) => {
+                Some(deadline.min(last_start + frame_time))
+            }
+            (deadline, _, Some(_)) => deadline,
+            (_, _, None) => None,
+        };
+    }
+
+    /// Starts a frame, scheduling the next one `frame_time` later, or as soon as possible without
+    /// a limit. Returns how far the time since the last frame was off from `frame_time`.
+    pub fn begin_frame(&mut self, frame_time: Option<Duration>) -> Option<Duration> {
+        self.begin_frame_at(Instant::now(), frame_time)
+    }
+
+    fn begin_frame_at(&mut self, now: Instant, frame_time: Option<Duration>) -> Option<Duration> {
+        let jitter = match (self.last_start, frame_time) {
+            (Some(last_start), Some(frame_time)) if self.deadline.is_some() => {
+                let elapsed = now - last_start;
+                Some(elapsed.max(frame_time) - elapsed.min(frame_time))
+            }
+            _ => None,
+        };
+
+        self.deadline = frame_time.map(|frame_time| match self.deadline {
+            Some(deadline) if deadline + frame_time > now => deadline + frame_time,
+            // the last frame overran, so the cadence starts over.
+            _ => now + frame_time,
+        });
+        self.last_start = Some(now);
+
+        jitter
+    }
+
+    /// Forgets the cadence, e.g. after being paused, so the next frame starts right away.
+    pub fn reset(&mut self) {
+        self.deadline = None;
+        self.last_start = None;
+    }
+}
+
+/// Whether `deadline` is far enough away to sleep rather than spin.
+fn too_early(now: Instant, deadline: Instant) -> bool {
+    now + SPIN_MARGIN < deadline
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: Duration = Duration::from_millis(16);
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn keeps_a_fixed_cadence() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::default();
+
+        assert_eq!(pacer.begin_frame_at(start, Some(FRAME)), None);
+        assert_eq!(pacer.deadline, Some(start + FRAME));
+
+        // starting a little late doesn't push the frames after it back.
+        assert_eq!(
+            pacer.begin_frame_at(start + ms(17), Some(FRAME)),
+            Some(ms(1))
+        );
+        assert_eq!(pacer.deadline, Some(start + FRAME * 2));
+    }
+
+    #[test]
+    fn sleeps_until_shortly_before_the_deadline() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::default();
+        pacer.begin_frame_at(start, Some(FRAME));
+
+        assert_eq!(
+            pacer.control_flow_at(start),
+            ControlFlow::WaitUntil(start + FRAME - SPIN_MARGIN)
+        );
+        assert_eq!(pacer.control_flow_at(start + ms(15)), ControlFlow::Poll);
+    }
+
+    #[test]
+    fn overruns_start_a_new_cadence() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::default();
+        pacer.begin_frame_at(start, Some(FRAME));
+
+        // two and a half frames late, the next frame is a whole frame away instead of due at once.
+        let late = start + ms(40);
+        assert_eq!(pacer.begin_frame_at(late, Some(FRAME)), Some(ms(24)));
+        assert_eq!(pacer.deadline, Some(late + FRAME));
+        assert!(matches!(
+            pacer.control_flow_at(late),
+            ControlFlow::WaitUntil(_)
+        ));
+    }
+
+    #[test]
+    fn unlimited_frames_start_right_away() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::default();
+
+        assert_eq!(pacer.begin_frame_at(start, None), None);
+        assert_eq!(pacer.begin_frame_at(start + ms(1), None), None);
+        assert_eq!(pacer.deadline, None);
+        assert_eq!(pacer.control_flow_at(start), ControlFlow::Poll);
+        assert!(pacer.wait(None));
+    }
+
+    #[test]
+    fn follows_the_limit_when_it_changes() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::default();
+
+        // in the background at 10 fps, then focused again at 60.
+        pacer.begin_frame_at(start, Some(ms(100)));
+        pacer.limit(Some(FRAME));
+        assert_eq!(pacer.deadline, Some(start + FRAME));
+
+        // a lower limit doesn't postpone the frame that is already scheduled.
+        pacer.limit(Some(ms(100)));
+        assert_eq!(pacer.deadline, Some(start + FRAME));
+
+        pacer.limit(None);
+        assert_eq!(pacer.deadline, None);
+    }
+
+    #[test]
+    fn reset_forgets_the_cadence() {
+        let start = Instant::now();
+        let mut pacer = FramePacer::default();
+        pacer.begin_frame_at(start, Some(FRAME));
+        pacer.reset();
+
+        assert_eq!(pacer.begin_frame_at(start + ms(500), Some(FRAME)), None);
+        assert_eq!(pacer.deadline, Some(start + ms(500) + FRAME));
+    }
+}
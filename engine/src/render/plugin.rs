@@ -0,0 +1,333 @@
+use std::{
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context as _;
+
+use super::{
+    app::{App, FrameworkHooks, Stage, System, World},
+    bundle::mesh::Bundles,
+    camera::{Camera, CameraBind, CameraPerspective},
+    framework::Framework,
+    raw::{IntoRawBinder, RawParams},
+    window::WindowId,
+};
+
+/// A piece of an [`App`], registering its systems and resources.
+pub trait Plugin {
+    fn build(self, app: &mut App);
+}
+
+/// Runs a [`Framework`] as part of an [`App`], stored as a resource of type `T`.
+///
+/// `init` runs in [`Stage::Startup`], `update` in [`Stage::FixedUpdate`], rendering the main
+/// window in [`Stage::Render`], resizing it in [`Stage::Resize`] and `shutdown` in
+/// [`Stage::Shutdown`], and its systems are all called `"framework"`. Every other hook is forwarded
+/// from the app. See [`App::set_tick_rate`], [`App::set_maximum_framerate`] and
+/// [`App::actions_mut`] for how the framework's settings combine with the app's own.
+pub struct FrameworkPlugin<T: Framework>(PhantomData<T>);
+
+impl<T: Framework> Default for FrameworkPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Framework> Plugin for FrameworkPlugin<T> {
+    fn build(self, app: &mut App) {
+        app.set_framework_hooks(FrameworkHooks {
+            tick_rate: |resources| resources.get::<T>().map(T::tick_rate),
+            action_map: |resources| resources.get::<T>().map(T::action_map),
+            render_window: |resources, ctx, id, encoder, view, targets, device, queue| {
+                if let Some(framework) = resources.get_mut::<T>() {
+                    framework.render_window(ctx, id, encoder, view, targets, device, queue);
+                }
+            },
+            resize_window: |resources, id, config, targets, device, queue| {
+                if let Some(framework) = resources.get_mut::<T>() {
+                    framework.resize_window(id, config, targets, device, queue);
+                }
+            },
+            window_closed: |resources, ctx, id| {
+                if let Some(framework) = resources.get_mut::<T>() {
+                    framework.window_closed(ctx, id);
+                }
+            },
+            maximum_framerate: |resources| resources.get::<T>().map(T::maximum_framerate),
+            background_framerate: |resources| resources.get::<T>().map(T::background_framerate),
+            window_title: |resources, stats| {
+                resources
+                    .get::<T>()
+                    .and_then(|framework| framework.window_title(stats))
+            },
+            screenshot_key: |resources| resources.get::<T>().and_then(T::screenshot_key),
+            wants_capture: |resources| resources.get_mut::<T>().is_some_and(T::wants_capture),
+            on_capture: |resources, image| {
+                if let Some(framework) = resources.get_mut::<T>() {
+                    framework.on_capture(image);
+                }
+            },
+            close_requested: |resources, ctx| {
+                resources
+                    .get_mut::<T>()
+                    .map(|framework| framework.close_requested(ctx))
+            },
+            focus_changed: |resources, ctx, focused| {
+                if let Some(framework) = resources.get_mut::<T>() {
+                    framework.focus_changed(ctx, focused);
+                }
+            },
+            suspended: |resources, ctx| {
+                if let Some(framework) = resources.get_mut::<T>() {
+                    framework.suspended(ctx);
+                }
+            },
+            resumed: |resources, ctx| {
+                if let Some(framework) = resources.get_mut::<T>() {
+                    framework.resumed(ctx);
+                }
+            },
+            input: |resources, device_id, event| {
+                if let Some(framework) = resources.get_mut::<T>() {
+                    framework.input(device_id, event);
+                }
+            },
+            on_event: |resources, event, control_flow| {
+                if let Some(framework) = resources.get_mut::<T>() {
+                    framework.on_event(event, control_flow);
+                }
+            },
+        })
+        .add_system(
+            Stage::Startup,
+            System::new("framework", |world: &mut World| -> anyhow::Result<()> {
+                let gpu = world
+                    .gpu()
+                    .context("the gpu is always available during startup")?;
+                let adapter = world
+                    .adapter
+                    .context("the adapter is only available during startup")?;
+
+                let framework = T::init(gpu.config, gpu.targets, adapter, gpu.device, gpu.queue)?;
+                world.resources.insert(framework);
+
+                Ok(())
+            }),
+        )
+        .add_system(
+            Stage::FixedUpdate,
+            System::new("framework", |world: &mut World| {
+                if let Some(framework) = world.resources.get_mut::<T>() {
+                    framework.update(world.ctx, world.fixed_dt);
+                }
+            }),
+        )
+        .add_system(
+            Stage::Resize,
+            System::new("framework", |world: &mut World| {
+                let Some(gpu) = world.gpu() else {
+                    return;
+                };
+
+                if let Some(framework) = world.resources.get_mut::<T>() {
+                    framework.resize_window(
+                        WindowId::MAIN,
+                        gpu.config,
+                        gpu.targets,
+                        gpu.device,
+                        gpu.queue,
+                    );
+                }
+            }),
+        )
+        .add_system(
+            Stage::Render,
+            System::new("framework", |world: &mut World| {
+                let (Some(gpu), Some(framework), Some(frame)) = (
+                    world.gpu(),
+                    world.resources.get_mut::<T>(),
+                    world.frame.as_mut(),
+                ) else {
+                    return;
+                };
+
+                framework.render_window(
+                    world.ctx,
+                    WindowId::MAIN,
+                    frame.encoder,
+                    frame.view,
+                    gpu.targets,
+                    gpu.device,
+                    gpu.queue,
+                );
+            }),
+        )
+        .add_system(
+            Stage::Shutdown,
+            System::new("framework", |world: &mut World| {
+                if let Some(framework) = world.resources.get_mut::<T>() {
+                    framework.shutdown(world.ctx);
+                }
+            }),
+        );
+    }
+}
+
+/// Keeps a [`Camera`] resource's view projection uploaded to a [`CameraBind`] resource, and its
+/// aspect ratio in line with the surface.
+///
+/// Its systems are called `"camera"`, the upload runs in [`Stage::PreRender`].
+pub struct CameraPlugin {
+    camera: Camera,
+}
+
+impl CameraPlugin {
+    pub fn new(camera: Camera) -> Self {
+        Self { camera }
+    }
+}
+
+impl Plugin for CameraPlugin {
+    fn build(self, app: &mut App) {
+        app.insert_resource(self.camera)
+            .add_system(
+                Stage::Startup,
+                System::new("camera", |world: &mut World| -> anyhow::Result<()> {
+                    let gpu = world
+                        .gpu()
+                        .context("the gpu is always available during startup")?;
+                    let perspective = CameraPerspective::new();
+                    let bind = perspective
+                        .create_raw_bind(gpu.device, bytemuck::cast_slice(&[perspective]));
+
+                    world.resources.insert(perspective);
+                    world.resources.insert(bind);
+
+                    Ok(())
+                }),
+            )
+            .add_system(
+                Stage::Resize,
+                System::new("camera", |world: &mut World| {
+                    let Some(gpu) = world.gpu() else {
+                        return;
+                    };
+
+                    if let Some(camera) = world.resources.get_mut::<Camera>() {
+                        camera.set_aspect(gpu.config.width as f32 / gpu.config.height as f32);
+                    }
+                }),
+            )
+            .add_system(
+                Stage::PreRender,
+                System::new("camera", |world: &mut World| {
+                    let (Some(gpu), Some(camera)) = (world.gpu(), world.resources.get::<Camera>())
+                    else {
+                        return;
+                    };
+
+                    let mut perspective = CameraPerspective::new();
+                    perspective.update_view_proj(camera);
+
+                    if let Some(bind) = world.resources.get_mut::<CameraBind>() {
+                        bind.update_buffer(gpu.queue, bytemuck::cast_slice(&[perspective]));
+                    }
+
+                    world.resources.insert(perspective);
+                }),
+            );
+    }
+}
+
+/// Keeps a [`Bundles`] resource, processing its queued bundles and instances every frame.
+///
+/// Needs the [`CameraBind`] of a [`CameraPlugin`]. Its system is called `"bundles"`, and runs in
+/// [`Stage::PreRender`] after `"camera"`.
+pub struct BundlesPlugin<T: IntoRawBinder + 'static>(PhantomData<T>);
+
+impl<T: IntoRawBinder + 'static> Default for BundlesPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: IntoRawBinder + 'static> Plugin for BundlesPlugin<T> {
+    fn build(self, app: &mut App) {
+        app.insert_resource(Bundles::<T>::default()).add_system(
+            Stage::PreRender,
+            System::new("bundles", |world: &mut World| {
+                let Some(gpu) = world.gpu() else {
+                    return;
+                };
+
+                // the bundles are taken out for a moment, as processing them needs the camera too.
+                let Some(mut bundles) = world.resources.remove::<Bundles<T>>() else {
+                    return;
+                };
+
+                if let Some(raw_camera) = world.resources.get::<CameraBind>() {
+                    let params: RawParams =
+                        (gpu.device, gpu.config, raw_camera, gpu.targets).into();
+                    bundles.process_queue(&params);
+                }
+
+                world.resources.insert(bundles);
+            })
+            .after("camera"),
+        );
+    }
+}
+
+/// Shows the [`FrameStats`](super::stats::FrameStats) summary in the window title, and optionally
+/// logs it. Its system is called `"stats"`, and runs in [`Stage::PostRender`].
+pub struct StatsPlugin {
+    /// How often the summary is refreshed.
+    pub interval: Duration,
+    pub show_in_title: bool,
+    pub log: bool,
+}
+
+impl Default for StatsPlugin {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            show_in_title: true,
+            log: false,
+        }
+    }
+}
+
+impl Plugin for StatsPlugin {
+    fn build(self, app: &mut App) {
+        let mut last_refresh = Instant::now();
+        let mut base_title = None;
+
+        app.add_system(
+            Stage::PostRender,
+            System::new("stats", move |world: &mut World| {
+                if last_refresh.elapsed() < self.interval {
+                    return;
+                }
+
+                let summary = world.ctx.stats().summary();
+
+                if self.show_in_title {
+                    let base_title =
+                        base_title.get_or_insert_with(|| world.ctx.window().title().to_string());
+
+                    world
+                        .ctx
+                        .window_mut()
+                        .set_title(format!("{base_title} [{summary}]"));
+                }
+
+                if self.log {
+                    log::info!("{summary}");
+                }
+
+                last_refresh = Instant::now();
+            }),
+        );
+    }
+}
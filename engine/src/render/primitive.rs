@@ -0,0 +1,454 @@
+use std::{collections::HashMap, f32::consts::PI};
+
+use glam::{Vec2, Vec3};
+
+use super::{mesh::Mesh, vertex::Vertex};
+
+/// Indexed meshes of common shapes, centered on the origin with Y up.
+///
+/// Triangles wind counter-clockwise when seen from outside, matching the back-face culling of
+/// [`PipelineBuilder`](super::builder::pipeline::PipelineBuilder). Segment counts below what a
+/// shape needs to be closed are raised to that minimum.
+impl Mesh {
+    /// A `width` by `height` quad in the XY plane, facing +Z.
+    pub fn quad(width: f32, height: f32) -> Self {
+        let mut geometry = Geometry::default();
+        geometry.face(
+            Vec3::ZERO,
+            Vec3::X * width / 2.0,
+            Vec3::Y * height / 2.0,
+            [1, 1],
+        );
+        geometry.into_mesh()
+    }
+
+    /// A `size` by `size` square in the XZ plane, facing +Y.
+    pub fn plane(size: f32) -> Self {
+        Self::grid(size, size, 1, 1)
+    }
+
+    /// A plane in the XZ plane facing +Y, split into `x_segments` by `z_segments` cells.
+    pub fn grid(width: f32, depth: f32, x_segments: u32, z_segments: u32) -> Self {
+        let mut geometry = Geometry::default();
+        geometry.face(
+            Vec3::ZERO,
+            Vec3::X * width / 2.0,
+            Vec3::NEG_Z * depth / 2.0,
+            [x_segments.max(1), z_segments.max(1)],
+        );
+        geometry.into_mesh()
+    }
+
+    /// A box with flat shaded sides, `size` along each axis.
+    pub fn cuboid(size: Vec3) -> Self {
+        let half = size / 2.0;
+        let mut geometry = Geometry::default();
+
+        // each side as its normal, and two edges whose cross product is that normal.
+        let sides = [
+            (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+            (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+            (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+            (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+            (Vec3::Z, Vec3::X, Vec3::Y),
+            (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+        ];
+
+        for (normal, u, v) in sides {
+            geometry.face(normal * half, u * half, v * half, [1, 1]);
+        }
+
+        geometry.into_mesh()
+    }
+
+    pub fn cube(size: f32) -> Self {
+        Self::cuboid(Vec3::splat(size))
+    }
+
+    /// A smooth sphere made of `sectors` slices around Y and `stacks` bands from pole to pole.
+    pub fn uv_sphere(radius: f32, sectors: u32, stacks: u32) -> Self {
+        let stacks = stacks.max(2);
+        let profile = (0..=stacks)
+            .map(|stack| {
+                let (sin, cos) = (PI * stack as f32 / stacks as f32).sin_cos();
+                ProfilePoint::new(Vec2::new(sin, cos) * radius, Vec2::new(sin, cos))
+            })
+            .collect::<Vec<_>>();
+
+        let mut geometry = Geometry::default();
+        geometry.lathe(&profile, sectors);
+        geometry.into_mesh()
+    }
+
+    /// A sphere made of evenly sized triangles, by splitting each triangle of an icosahedron in
+    /// four `subdivisions` times.
+    pub fn icosphere(radius: f32, subdivisions: u32) -> Self {
+        let t = (1.0 + 5f32.sqrt()) / 2.0;
+        let mut positions = [
+            [-1.0, t, 0.0],
+            [1.0, t, 0.0],
+            [-1.0, -t, 0.0],
+            [1.0, -t, 0.0],
+            [0.0, -1.0, t],
+            [0.0, 1.0, t],
+            [0.0, -1.0, -t],
+            [0.0, 1.0, -t],
+            [t, 0.0, -1.0],
+            [t, 0.0, 1.0],
+            [-t, 0.0, -1.0],
+            [-t, 0.0, 1.0],
+        ]
+        .map(|position| Vec3::from(position).normalize())
+        .to_vec();
+
+        let mut triangles = vec![
+            [0, 11, 5],
+            [0, 5, 1],
+            [0, 1, 7],
+            [0, 7, 10],
+            [0, 10, 11],
+            [1, 5, 9],
+            [5, 11, 4],
+            [11, 10, 2],
+            [10, 7, 6],
+            [7, 1, 8],
+            [3, 9, 4],
+            [3, 4, 2],
+            [3, 2, 6],
+            [3, 6, 8],
+            [3, 8, 9],
+            [4, 9, 5],
+            [2, 4, 11],
+            [6, 2, 10],
+            [8, 6, 7],
+            [9, 8, 1],
+        ];
+
+        for _ in 0..subdivisions {
+            // neighbouring triangles share the vertex in the middle of their shared edge.
+            let mut midpoints = HashMap::new();
+            let mut midpoint = |a: u32, b: u32| {
+                *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let position = (positions[a as usize] + positions[b as usize]).normalize();
+                    positions.push(position);
+                    positions.len() as u32 - 1
+                })
+            };
+
+            triangles = triangles
+                .into_iter()
+                .flat_map(|[a, b, c]| {
+                    let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                    [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+                })
+                .collect();
+        }
+
+        let vertices = positions
+            .into_iter()
+            .map(|normal| vertex(normal * radius, normal))
+            .collect();
+
+        Mesh::builder()
+            .vertices(vertices)
+            .indices(triangles.concat())
+            .build()
+    }
+
+    /// A capped cylinder along Y, with `segments` sides.
+    pub fn cylinder(radius: f32, height: f32, segments: u32) -> Self {
+        let half = height / 2.0;
+        let mut geometry = Geometry::default();
+
+        geometry.lathe(
+            &[
+                ProfilePoint::new(Vec2::new(radius, half), Vec2::X),
+                ProfilePoint::new(Vec2::new(radius, -half), Vec2::X),
+            ],
+            segments,
+        );
+        geometry.disc(half, radius, segments, true);
+        geometry.disc(-half, radius, segments, false);
+        geometry.into_mesh()
+    }
+
+    /// A cone along Y with its tip at the top, with `segments` sides.
+    pub fn cone(radius: f32, height: f32, segments: u32) -> Self {
+        let half = height / 2.0;
+        let normal = Vec2::new(height, radius).normalize_or_zero();
+        let mut geometry = Geometry::default();
+
+        geometry.lathe(
+            &[
+                ProfilePoint::new(Vec2::new(0.0, half), normal),
+                ProfilePoint::new(Vec2::new(radius, -half), normal),
+            ],
+            segments,
+        );
+        geometry.disc(-half, radius, segments, false);
+        geometry.into_mesh()
+    }
+
+    /// A ring in the XZ plane, `radius` from the center to the middle of the tube. `segments`
+    /// go around the ring and `sides` around the tube.
+    pub fn torus(radius: f32, tube_radius: f32, segments: u32, sides: u32) -> Self {
+        let sides = sides.max(3);
+        // clockwise, so the outside of the tube runs from top to bottom like other profiles.
+        let profile = (0..=sides)
+            .map(|side| {
+                let (sin, cos) = (-2.0 * PI * side as f32 / sides as f32).sin_cos();
+                let normal = Vec2::new(cos, sin);
+                ProfilePoint::new(Vec2::X * radius + normal * tube_radius, normal)
+            })
+            .collect::<Vec<_>>();
+
+        let mut geometry = Geometry::default();
+        geometry.lathe(&profile, segments);
+        geometry.into_mesh()
+    }
+
+    /// A cylinder along Y with half spheres for caps. `height` is that of the cylinder between
+    /// them, and each cap is made of `rings` bands.
+    pub fn capsule(radius: f32, height: f32, segments: u32, rings: u32) -> Self {
+        let half = height / 2.0;
+        let rings = rings.max(1);
+
+        let cap = |offset: f32, from: u32| {
+            (0..=rings).map(move |ring| {
+                let angle = PI / 2.0 * (from + ring) as f32 / rings as f32;
+                let normal = Vec2::new(angle.sin(), angle.cos());
+                ProfilePoint::new(normal * radius + Vec2::Y * offset, normal)
+            })
+        };
+
+        let profile = cap(half, 0).chain(cap(-half, rings)).collect::<Vec<_>>();
+
+        let mut geometry = Geometry::default();
+        geometry.lathe(&profile, segments);
+        geometry.into_mesh()
+    }
+}
+
+fn vertex(position: Vec3, normal: Vec3) -> Vertex {
+    Vertex::builder()
+        .position(position.to_array())
+        .normal(normal.to_array())
+        .build()
+}
+
+/// A point of a shape's outline, as distance from the Y axis and height, to be revolved around Y.
+#[derive(Debug, Clone, Copy)]
+struct ProfilePoint {
+    position: Vec2,
+    normal: Vec2,
+}
+
+impl ProfilePoint {
+    fn new(position: Vec2, normal: Vec2) -> Self {
+        Self { position, normal }
+    }
+}
+
+#[derive(Default)]
+struct Geometry {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+}
+
+impl Geometry {
+    fn push(&mut self, position: Vec3, normal: Vec3) -> u32 {
+        self.vertices.push(vertex(position, normal));
+        self.vertices.len() as u32 - 1
+    }
+
+    /// A flat rectangle around `center` spanning `u` and `v` either way, facing `u` cross `v`.
+    fn face(&mut self, center: Vec3, u: Vec3, v: Vec3, [u_segments, v_segments]: [u32; 2]) {
+        let normal = u.cross(v).normalize_or_zero();
+        let first = self.vertices.len() as u32;
+        let row = u_segments + 1;
+
+        for y in 0..=v_segments {
+            for x in 0..=u_segments {
+                let s = x as f32 / u_segments as f32 * 2.0 - 1.0;
+                let t = y as f32 / v_segments as f32 * 2.0 - 1.0;
+                self.push(center + u * s + v * t, normal);
+            }
+        }
+
+        for y in 0..v_segments {
+            for x in 0..u_segments {
+                let a = first + y * row + x;
+                let (b, c, d) = (a + 1, a + row + 1, a + row);
+                self.indices.extend([a, b, c, a, c, d]);
+            }
+        }
+    }
+
+    /// Revolves `profile`, which runs from top to bottom, around Y in `segments` steps. Points on
+    /// the axis become poles without zero-area triangles.
+    fn lathe(&mut self, profile: &[ProfilePoint], segments: u32) {
+        let segments = segments.max(3);
+        let first = self.vertices.len() as u32;
+        let row = segments + 1;
+
+        for point in profile {
+            // the seam is duplicated, so every ring has a vertex at its start and end.
+            for segment in 0..=segments {
+                let (sin, cos) = (2.0 * PI * segment as f32 / segments as f32).sin_cos();
+                let around = Vec3::new(cos, 0.0, -sin);
+
+                self.push(
+                    around * point.position.x + Vec3::Y * point.position.y,
+                    (around * point.normal.x + Vec3::Y * point.normal.y).normalize_or_zero(),
+                );
+            }
+        }
+
+        // points the generators put on the axis end up a rounding error away from it, which
+        // grows with the size of the shape.
+        let extent = profile
+            .iter()
+            .map(|point| point.position.abs().max_element())
+            .fold(0.0, f32::max);
+        let on_axis = |point: &ProfilePoint| point.position.x.abs() <= extent * 1e-5;
+
+        for (ring, points) in profile.windows(2).enumerate() {
+            let (top, bottom) = (!on_axis(&points[0]), !on_axis(&points[1]));
+
+            for segment in 0..segments {
+                let a = first + ring as u32 * row + segment;
+                let (b, c, d) = (a + 1, a + row + 1, a + row);
+
+                if top {
+                    self.indices.extend([a, d, b]);
+                }
+
+                if bottom {
+                    self.indices.extend([b, d, c]);
+                }
+            }
+        }
+    }
+
+    /// A flat disc at height `y`, facing up or down.
+    fn disc(&mut self, y: f32, radius: f32, segments: u32, up: bool) {
+        let segments = segments.max(3);
+        let normal = match up {
+            true => Vec3::Y,
+            false => Vec3::NEG_Y,
+        };
+
+        let center = self.push(Vec3::Y * y, normal);
+
+        for segment in 0..segments {
+            let (sin, cos) = (2.0 * PI * segment as f32 / segments as f32).sin_cos();
+            self.push(Vec3::new(cos * radius, y, -sin * radius), normal);
+        }
+
+        for segment in 0..segments {
+            let a = center + 1 + segment;
+            let b = center + 1 + (segment + 1) % segments;
+
+            match up {
+                true => self.indices.extend([center, a, b]),
+                false => self.indices.extend([center, b, a]),
+            }
+        }
+    }
+
+    fn into_mesh(self) -> Mesh {
+        Mesh::builder()
+            .vertices(self.vertices)
+            .indices(self.indices)
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks the counts, that normals are unit length and that no triangle is degenerate.
+    fn check(mesh: &Mesh, vertices: usize, indices: usize) {
+        assert_eq!(mesh.vertices.len(), vertices);
+        assert_eq!(mesh.triangle_indices().len(), indices);
+
+        for vertex in &mesh.vertices {
+            let length = Vec3::from(vertex.normal).length();
+            assert!((length - 1.0).abs() < 1e-4, "normal of length {length}");
+        }
+
+        for triangle in mesh.triangle_indices().chunks_exact(3) {
+            let [a, b, c] = [triangle[0], triangle[1], triangle[2]]
+                .map(|idx| Vec3::from(mesh.vertices[idx as usize].position));
+            assert!(
+                (b - a).cross(c - a).length() > 0.0,
+                "{triangle:?} has no area"
+            );
+        }
+    }
+
+    #[test]
+    fn quad() {
+        check(&Mesh::quad(2.0, 1.0), 4, 6);
+    }
+
+    #[test]
+    fn plane() {
+        check(&Mesh::plane(2.0), 4, 6);
+    }
+
+    #[test]
+    fn grid() {
+        check(&Mesh::grid(3.0, 2.0, 3, 2), 12, 36);
+        check(&Mesh::grid(1.0, 1.0, 0, 0), 4, 6);
+    }
+
+    #[test]
+    fn cuboid() {
+        check(&Mesh::cuboid(Vec3::new(1.0, 2.0, 3.0)), 24, 36);
+    }
+
+    #[test]
+    fn cube() {
+        check(&Mesh::cube(1.0), 24, 36);
+    }
+
+    #[test]
+    fn uv_sphere() {
+        // the poles only get one triangle per segment.
+        for radius in [0.5, 1.0, 1.5, 100.0] {
+            check(&Mesh::uv_sphere(radius, 8, 4), 45, 144);
+        }
+    }
+
+    #[test]
+    fn icosphere() {
+        check(&Mesh::icosphere(1.0, 0), 12, 60);
+        check(&Mesh::icosphere(2.0, 2), 162, 960);
+    }
+
+    #[test]
+    fn cylinder() {
+        check(&Mesh::cylinder(1.0, 2.0, 8), 36, 96);
+    }
+
+    #[test]
+    fn cone() {
+        for radius in [1.0, 10.0] {
+            check(&Mesh::cone(radius, 2.0, 8), 27, 48);
+        }
+    }
+
+    #[test]
+    fn torus() {
+        check(&Mesh::torus(2.0, 0.5, 8, 6), 63, 288);
+    }
+
+    #[test]
+    fn capsule() {
+        for radius in [0.5, 5.0] {
+            check(&Mesh::capsule(radius, 2.0, 8, 3), 72, 288);
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+/// Timestamps available per frame, two for every scope.
+pub const MAX_QUERIES: u32 = 256;
+
+/// Frames that may be waiting on their timestamps at once. Scopes opened while this many frames
+/// are still in flight aren't measured, rather than stalling the GPU.
+pub const MAX_FRAMES_IN_FLIGHT: usize = 4;
+
+/// How long a named scope took on the GPU.
+#[derive(Debug, Clone)]
+pub struct ScopeTiming {
+    pub name: String,
+    pub duration: Duration,
+}
+
+/// A scope opened with [`GpuProfiler::begin_scope`], close it with [`GpuProfiler::end_scope`].
+#[must_use]
+pub struct GpuScope(Option<usize>);
+
+/// Measures render and compute passes with timestamp queries.
+///
+/// Scopes are written around passes on the command encoder, and read back a few frames later
+/// without waiting on the GPU. Without [`wgpu::Features::TIMESTAMP_QUERY`], every method is a no-op.
+///
+/// Passes begun through [`RenderPassBuilder::begin_profiled`] or
+/// [`ComputePassBuilder::begin_profiled`] are measured without pairing scopes by hand:
+///
+/// ```ignore
+/// let mut render_pass = RenderPassBuilder::builder()
+///     /* ... */
+///     .build()
+///     .begin_profiled(encoder, &mut ctx.profiler, "voxel pass");
+/// ```
+///
+/// Anything else can be wrapped in a scope:
+///
+/// ```ignore
+/// let scope = ctx.profiler.begin_scope("copy", encoder);
+/// encoder.copy_texture_to_texture(/* ... */);
+/// ctx.profiler.end_scope(scope, encoder);
+/// ```
+///
+/// [`RenderPassBuilder::begin_profiled`]: super::builder::pass::RenderPassBuilder::begin_profiled
+/// [`ComputePassBuilder::begin_profiled`]: super::builder::pass::ComputePassBuilder::begin_profiled
+pub struct GpuProfiler {
+    queries: Option<Queries>,
+    scopes: Vec<OpenScope>,
+    // the scope of a pass, which is closed the next time the profiler gets the encoder.
+    pass_scope: Option<usize>,
+    next_query: u32,
+    pending: VecDeque<PendingFrame>,
+    free_buffers: Vec<wgpu::Buffer>,
+    results: Vec<ScopeTiming>,
+}
+
+struct Queries {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    period: f32,
+}
+
+struct OpenScope {
+    name: String,
+    start: u32,
+    end: Option<u32>,
+}
+
+struct PendingFrame {
+    buffer: wgpu::Buffer,
+    scopes: Vec<OpenScope>,
+    map_state: Arc<AtomicU8>,
+    map_requested: bool,
+}
+
+const MAP_PENDING: u8 = 0;
+const MAP_DONE: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+impl GpuProfiler {
+    /// A profiler that never measures anything.
+    pub fn disabled() -> Self {
+        Self {
+            queries: None,
+            scopes: Vec::new(),
+            pass_scope: None,
+            next_query: 0,
+            pending: VecDeque::new(),
+            free_buffers: Vec::new(),
+            results: Vec::new(),
+        }
+    }
+
+    /// A profiler for `device`, which is disabled if the device lacks [`wgpu::Features::TIMESTAMP_QUERY`].
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            log::info!("timestamp queries are unsupported, GPU profiling is disabled");
+            return Self::disabled();
+        }
+
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("profiler queries"),
+            ty: wgpu::QueryType::Timestamp,
+            count: MAX_QUERIES,
+        });
+
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("profiler resolve buffer"),
+            size: Self::buffer_size(),
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            queries: Some(Queries {
+                query_set,
+                resolve_buffer,
+                period: queue.get_timestamp_period(),
+            }),
+            ..Self::disabled()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.queries.is_some()
+    }
+
+    /// The most recent scope timings that have been read back, in the order the scopes were opened.
+    pub fn results(&self) -> &[ScopeTiming] {
+        &self.results
+    }
+
+    pub fn begin_scope(
+        &mut self,
+        name: impl Into<String>,
+        encoder: &mut wgpu::CommandEncoder,
+    ) -> GpuScope {
+        self.close_pass_scope(encoder);
+
+        let Some(queries) = &self.queries else {
+            return GpuScope(None);
+        };
+
+        if self.next_query + 2 > MAX_QUERIES || self.pending.len() >= MAX_FRAMES_IN_FLIGHT {
+            return GpuScope(None);
+        }
+
+        encoder.write_timestamp(&queries.query_set, self.next_query);
+
+        self.scopes.push(OpenScope {
+            name: name.into(),
+            start: self.next_query,
+            end: None,
+        });
+        self.next_query += 1;
+
+        GpuScope(Some(self.scopes.len() - 1))
+    }
+
+    pub fn end_scope(&mut self, scope: GpuScope, encoder: &mut wgpu::CommandEncoder) {
+        self.close_pass_scope(encoder);
+
+        let (Some(queries), Some(idx)) = (&self.queries, scope.0) else {
+            return;
+        };
+
+        encoder.write_timestamp(&queries.query_set, self.next_query);
+
+        self.scopes[idx].end = Some(self.next_query);
+        self.next_query += 1;
+    }
+
+    /// Resolves this frame's timestamps into a readback buffer. Call this once per frame, after
+    /// the last scope has been closed and before the encoder is submitted.
+    pub fn resolve(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder) {
+        self.close_pass_scope(encoder);
+
+        let Some(queries) = &self.queries else {
+            return;
+        };
+
+        if self.next_query == 0 {
+            return;
+        }
+
+        let buffer = self.free_buffers.pop().unwrap_or_else(|| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("profiler readback buffer"),
+                size: Self::buffer_size(),
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            })
+        });
+
+        encoder.resolve_query_set(
+            &queries.query_set,
+            0..self.next_query,
+            &queries.resolve_buffer,
+            0,
+        );
+        encoder.copy_buffer_to_buffer(
+            &queries.resolve_buffer,
+            0,
+            &buffer,
+            0,
+            (self.next_query * wgpu::QUERY_SIZE) as wgpu::BufferAddress,
+        );
+
+        self.pending.push_back(PendingFrame {
+            buffer,
+            scopes: std::mem::take(&mut self.scopes),
+            map_state: Arc::new(AtomicU8::new(MAP_PENDING)),
+            map_requested: false,
+        });
+        self.next_query = 0;
+    }
+
+    /// Starts mapping the frames that were just submitted, and collects the timings of any
+    /// frame whose timestamps are available by now. Call this once per frame, after submitting.
+    pub fn end_frame(&mut self, device: &wgpu::Device) {
+        let Some(queries) = &self.queries else {
+            return;
+        };
+
+        // scopes that were never resolved can't be measured anymore.
+        self.scopes.clear();
+        self.pass_scope = None;
+        self.next_query = 0;
+
+        for frame in self.pending.iter_mut().filter(|frame| !frame.map_requested) {
+            let map_state = frame.map_state.clone();
+
+            frame
+                .buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    let state = if result.is_ok() { MAP_DONE } else { MAP_FAILED };
+                    map_state.store(state, Ordering::Release);
+                });
+            frame.map_requested = true;
+        }
+
+        device.poll(wgpu::Maintain::Poll);
+
+        while let Some(frame) = self.pending.front() {
+            match frame.map_state.load(Ordering::Acquire) {
+                MAP_PENDING => break,
+                MAP_FAILED => {
+                    self.pending.pop_front();
+                    continue;
+                }
+                _ => (),
+            }
+
+            let frame = self.pending.pop_front().unwrap();
+
+            {
+                let data = frame.buffer.slice(..).get_mapped_range();
+                let timestamps: &[u64] = bytemuck::cast_slice(&data);
+
+                self.results = frame
+                    .scopes
+                    .iter()
+                    .filter_map(|scope| {
+                        let start = timestamps[scope.start as usize];
+                        let end = timestamps[scope.end? as usize];
+                        let nanos = end.saturating_sub(start) as f64 * queries.period as f64;
+
+                        Some(ScopeTiming {
+                            name: scope.name.clone(),
+                            duration: Duration::from_nanos(nanos as u64),
+                        })
+                    })
+                    .collect();
+            }
+
+            frame.buffer.unmap();
+            self.free_buffers.push(frame.buffer);
+        }
+    }
+
+    /// Opens a scope for a pass that is about to begin on `encoder`. As the pass borrows the
+    /// encoder until it's dropped, the scope is closed by whatever next hands the profiler the
+    /// encoder: another scope, or [`GpuProfiler::resolve`] at the end of the frame.
+    pub fn begin_pass_scope(
+        &mut self,
+        name: impl Into<String>,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        let scope = self.begin_scope(name, encoder);
+        self.pass_scope = scope.0;
+    }
+
+    fn close_pass_scope(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if let Some(idx) = self.pass_scope.take() {
+            self.end_scope(GpuScope(Some(idx)), encoder);
+        }
+    }
+
+    fn buffer_size() -> wgpu::BufferAddress {
+        (MAX_QUERIES * wgpu::QUERY_SIZE) as wgpu::BufferAddress
+    }
+}
@@ -1,6 +1,6 @@
 use typed_builder::TypedBuilder;
 
-use super::camera::CameraBind;
+use super::{camera::CameraBind, target::RenderTargets};
 
 pub mod storage;
 
@@ -9,6 +9,7 @@ pub struct RawParams<'a> {
     pub device: &'a wgpu::Device,
     pub config: &'a wgpu::SurfaceConfiguration,
     pub raw_camera: &'a CameraBind,
+    pub targets: &'a RenderTargets,
 }
 
 impl<'a>
@@ -16,19 +17,22 @@ impl<'a>
         &'a wgpu::Device,
         &'a wgpu::SurfaceConfiguration,
         &'a CameraBind,
+        &'a RenderTargets,
     )> for RawParams<'a>
 {
     fn from(
-        (device, config, raw_camera): (
+        (device, config, raw_camera, targets): (
             &'a wgpu::Device,
             &'a wgpu::SurfaceConfiguration,
             &'a CameraBind,
+            &'a RenderTargets,
         ),
     ) -> Self {
         Self {
             device,
             config,
             raw_camera,
+            targets,
         }
     }
 }
@@ -45,6 +49,7 @@ where
     Self: Sized,
 {
     type RawBinder: RawBinder;
+    #[allow(clippy::wrong_self_convention)]
     fn into_raw(&self, params: &RawParams) -> Self::RawBinder;
 }
 
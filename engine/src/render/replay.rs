@@ -0,0 +1,579 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind, Read, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context as _};
+use serde::{Deserialize, Serialize};
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{
+        DeviceEvent, DeviceId, ElementState, KeyboardInput, ModifiersState, MouseButton,
+        MouseScrollDelta, TouchPhase, WindowEvent,
+    },
+    event_loop::ControlFlow,
+};
+
+use super::{context::Context, framework::Framework, window::WindowId};
+
+const MAGIC: &[u8; 4] = b"RPLY";
+const VERSION: u32 = 1;
+/// The most a single frame of a recording may take up, so a corrupt length can't make loading
+/// allocate without bound.
+const MAX_FRAME_SIZE: u64 = 16 * 1024 * 1024;
+
+/// The device id replayed events are delivered with, which is the same for every one of them.
+///
+/// winit can't make up a device id other than [`DeviceId::dummy`], which is only unsafe to pass
+/// back into winit. The engine never does, and frameworks only get to compare it.
+pub fn replay_device_id() -> DeviceId {
+    unsafe { DeviceId::dummy() }
+}
+
+/// The options recordings are written with, which are those of `bincode::serialize_into`, with
+/// a size limit for reading.
+fn bincode_options() -> impl bincode::Options {
+    use bincode::Options;
+
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .with_limit(MAX_FRAME_SIZE)
+}
+
+/// The window and device events a [`Recording`] keeps, which are the ones input handling uses.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RecordedEvent {
+    Keyboard(KeyboardInput),
+    MouseInput {
+        state: ElementState,
+        button: MouseButton,
+    },
+    MouseWheel(MouseScrollDelta),
+    CursorMoved {
+        x: f64,
+        y: f64,
+    },
+    CursorLeft,
+    ModifiersChanged(ModifiersState),
+    ReceivedCharacter(char),
+    Focused(bool),
+    Resized {
+        width: u32,
+        height: u32,
+    },
+    CloseRequested,
+    /// A [`DeviceEvent::MouseMotion`], the only device event that is recorded.
+    MouseMotion {
+        x: f64,
+        y: f64,
+    },
+}
+
+impl RecordedEvent {
+    pub fn from_window_event(event: &WindowEvent) -> Option<Self> {
+        Some(match event {
+            WindowEvent::KeyboardInput { input, .. } => RecordedEvent::Keyboard(*input),
+            WindowEvent::MouseInput { state, button, .. } => RecordedEvent::MouseInput {
+                state: *state,
+                button: *button,
+            },
+            WindowEvent::MouseWheel { delta, .. } => RecordedEvent::MouseWheel(*delta),
+            WindowEvent::CursorMoved { position, .. } => RecordedEvent::CursorMoved {
+                x: position.x,
+                y: position.y,
+            },
+            WindowEvent::CursorLeft { .. } => RecordedEvent::CursorLeft,
+            WindowEvent::ModifiersChanged(modifiers) => RecordedEvent::ModifiersChanged(*modifiers),
+            WindowEvent::ReceivedCharacter(character) => {
+                RecordedEvent::ReceivedCharacter(*character)
+            }
+            WindowEvent::Focused(focused) => RecordedEvent::Focused(*focused),
+            WindowEvent::Resized(size) => RecordedEvent::Resized {
+                width: size.width,
+                height: size.height,
+            },
+            WindowEvent::CloseRequested => RecordedEvent::CloseRequested,
+            _ => return None,
+        })
+    }
+
+    pub fn from_device_event(event: &DeviceEvent) -> Option<Self> {
+        match event {
+            DeviceEvent::MouseMotion { delta: (x, y) } => {
+                Some(RecordedEvent::MouseMotion { x: *x, y: *y })
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether this is keyboard or mouse input, rather than something happening to the window.
+    pub fn is_input(&self) -> bool {
+        !matches!(
+            self,
+            RecordedEvent::Focused(_)
+                | RecordedEvent::Resized { .. }
+                | RecordedEvent::CloseRequested
+        )
+    }
+
+    /// The window event this was recorded from, with [`replay_device_id`] and `modifiers` for
+    /// the fields that weren't recorded.
+    #[allow(deprecated)]
+    pub fn to_window_event(self, modifiers: ModifiersState) -> Option<WindowEvent<'static>> {
+        let device_id = replay_device_id();
+
+        Some(match self {
+            RecordedEvent::Keyboard(input) => WindowEvent::KeyboardInput {
+                device_id,
+                input,
+                is_synthetic: false,
+            },
+            RecordedEvent::MouseInput { state, button } => WindowEvent::MouseInput {
+                device_id,
+                state,
+                button,
+                modifiers,
+            },
+            RecordedEvent::MouseWheel(delta) => WindowEvent::MouseWheel {
+                device_id,
+                delta,
+                phase: TouchPhase::Moved,
+                modifiers,
+            },
+            RecordedEvent::CursorMoved { x, y } => WindowEvent::CursorMoved {
+                device_id,
+                position: PhysicalPosition::new(x, y),
+                modifiers,
+            },
+            RecordedEvent::CursorLeft => WindowEvent::CursorLeft { device_id },
+            RecordedEvent::ModifiersChanged(modifiers) => WindowEvent::ModifiersChanged(modifiers),
+            RecordedEvent::ReceivedCharacter(character) => {
+                WindowEvent::ReceivedCharacter(character)
+            }
+            RecordedEvent::Focused(focused) => WindowEvent::Focused(focused),
+            RecordedEvent::Resized { width, height } => {
+                WindowEvent::Resized(PhysicalSize::new(width, height))
+            }
+            RecordedEvent::CloseRequested => WindowEvent::CloseRequested,
+            RecordedEvent::MouseMotion { .. } => return None,
+        })
+    }
+
+    pub fn to_device_event(self) -> Option<DeviceEvent> {
+        match self {
+            RecordedEvent::MouseMotion { x, y } => Some(DeviceEvent::MouseMotion { delta: (x, y) }),
+            _ => None,
+        }
+    }
+}
+
+/// An event, and when it arrived in microseconds since the recording started.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TimedEvent {
+    pub at: u64,
+    pub window: WindowId,
+    pub event: RecordedEvent,
+}
+
+/// Everything that happened before a frame, and how many fixed-step ticks it ran.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordedFrame {
+    /// When the frame started, in microseconds since the recording started.
+    pub at: u64,
+    pub ticks: u32,
+    pub events: Vec<TimedEvent>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    tick_hz: f64,
+}
+
+/// Input and fixed-step boundaries of a run, as recorded through [`EngineConfig::record`].
+///
+/// Replaying one, through [`EngineConfig::replay`] or
+/// [`replay_headless`](super::headless::replay_headless), feeds the same events to the framework
+/// at the same frames, with a simulated clock that advances like the recorded one did.
+///
+/// [`EngineConfig::record`]: super::config::EngineConfig::record
+/// [`EngineConfig::replay`]: super::config::EngineConfig::replay
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Recording {
+    /// The [`TickRate`](super::time::TickRate) the recording was made with.
+    pub tick_hz: f64,
+    pub frames: Vec<RecordedFrame>,
+}
+
+impl Recording {
+    /// Loads a recording, up to the last complete frame if the recording was cut short. Frames
+    /// larger than 16 MiB are taken for corruption.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        use bincode::Options;
+
+        let path = path.as_ref();
+        let file = File::open(path).with_context(|| format!("unable to open {path:?}"))?;
+        let mut reader = BufReader::new(file);
+
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+
+        if &magic != MAGIC {
+            bail!("{path:?} isn't a recording");
+        }
+
+        let header: Header = bincode_options().deserialize_from(&mut reader)?;
+
+        if header.version != VERSION {
+            bail!(
+                "{path:?} is a version {} recording, only version {VERSION} is supported",
+                header.version
+            );
+        }
+
+        let mut frames = Vec::new();
+
+        loop {
+            match bincode_options().deserialize_from(&mut reader) {
+                Ok(frame) => frames.push(frame),
+                Err(err) => match *err {
+                    // a recording is written as it goes, so a crash leaves it cut off mid-frame.
+                    bincode::ErrorKind::Io(err) if err.kind() == ErrorKind::UnexpectedEof => break,
+                    err => return Err(err.into()),
+                },
+            }
+        }
+
+        Ok(Self {
+            tick_hz: header.tick_hz,
+            frames,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let mut writer = create_recording(path.as_ref(), self.tick_hz)?;
+
+        for frame in &self.frames {
+            bincode::serialize_into(&mut writer, frame)?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn duration(&self) -> Duration {
+        let at = self.frames.last().map(|frame| frame.at).unwrap_or_default();
+        Duration::from_micros(at)
+    }
+}
+
+fn create_recording(path: &Path, tick_hz: f64) -> anyhow::Result<BufWriter<File>> {
+    let file = File::create(path).with_context(|| format!("unable to create {path:?}"))?;
+    let mut writer = BufWriter::new(file);
+
+    writer.write_all(MAGIC)?;
+    bincode::serialize_into(
+        &mut writer,
+        &Header {
+            version: VERSION,
+            tick_hz,
+        },
+    )?;
+
+    Ok(writer)
+}
+
+/// Writes a [`Recording`] frame by frame, so it survives the application crashing.
+pub(crate) struct Recorder {
+    writer: Option<BufWriter<File>>,
+    start: Instant,
+    events: Vec<TimedEvent>,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, tick_hz: f64) -> anyhow::Result<Self> {
+        Ok(Self {
+            writer: Some(create_recording(path, tick_hz)?),
+            start: Instant::now(),
+            events: Vec::new(),
+        })
+    }
+
+    pub fn record(&mut self, window: WindowId, event: RecordedEvent) {
+        self.events.push(TimedEvent {
+            at: self.start.elapsed().as_micros() as u64,
+            window,
+            event,
+        });
+    }
+
+    /// Writes a frame with every event recorded since the last one.
+    pub fn frame(&mut self, ticks: u32) {
+        let Some(writer) = &mut self.writer else {
+            return;
+        };
+
+        let frame = RecordedFrame {
+            at: self.start.elapsed().as_micros() as u64,
+            ticks,
+            events: std::mem::take(&mut self.events),
+        };
+
+        let written = bincode::serialize_into(&mut *writer, &frame)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| writer.flush().map_err(anyhow::Error::from));
+
+        if let Err(err) = written {
+            log::error!("unable to write the recording, it stops here: {err}");
+            self.writer = None;
+        }
+    }
+}
+
+/// Plays a [`Recording`] back into a framework, one frame at a time.
+pub(crate) struct Replay {
+    frames: std::vec::IntoIter<RecordedFrame>,
+    last_at: u64,
+}
+
+impl Replay {
+    pub fn new(recording: Recording, tick_hz: f64) -> Self {
+        if recording.tick_hz != tick_hz {
+            log::warn!(
+                "the recording ticks at {}hz but the framework at {tick_hz}hz, it may not replay the same",
+                recording.tick_hz
+            );
+        }
+
+        Self {
+            frames: recording.frames.into_iter(),
+            last_at: 0,
+        }
+    }
+
+    /// Feeds the next frame's events to `framework`, and sets the clock to the frame's delta.
+    /// Returns the ticks the frame ran, or `None` once the recording is over.
+    ///
+    /// Events go to the input state and [`Framework::on_event`] or [`Framework::input`] like live
+    /// ones, with [`replay_device_id`] as their device. The engine itself doesn't act on them, so a
+    /// recorded resize or close request doesn't resize or close anything. Events recorded for a
+    /// window that isn't open are dropped.
+    pub fn next_frame<T: Framework>(
+        &mut self,
+        framework: &mut T,
+        ctx: &mut Context,
+        control_flow: &mut ControlFlow,
+    ) -> Option<u32> {
+        let frame = self.frames.next()?;
+
+        for timed in frame.events {
+            if ctx.window_by_id(timed.window).is_none() {
+                log::debug!(
+                    "dropping a replayed event for {:?}, which isn't open",
+                    timed.window
+                );
+                continue;
+            }
+
+            ctx.input.handle_recorded_event(&timed.event);
+
+            if let Some(event) = timed.event.to_device_event() {
+                framework.input(replay_device_id(), event);
+            } else if let Some(event) = timed.event.to_window_event(ctx.input.modifiers()) {
+                framework.on_event(event, control_flow);
+            }
+        }
+
+        let delta = frame.at.saturating_sub(self.last_at);
+        ctx.time.set_fixed_delta(Some(Duration::from_micros(delta)));
+        self.last_at = frame.at;
+
+        Some(frame.ticks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use winit::event::VirtualKeyCode;
+
+    use super::*;
+    use crate::render::{target::RenderTargets, time::Time};
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("engine-{}-{name}.rply", std::process::id()))
+    }
+
+    #[allow(deprecated)]
+    fn key(key: VirtualKeyCode, state: ElementState) -> RecordedEvent {
+        RecordedEvent::Keyboard(KeyboardInput {
+            scancode: 0,
+            state,
+            virtual_keycode: Some(key),
+            modifiers: ModifiersState::empty(),
+        })
+    }
+
+    #[derive(Default)]
+    struct Collect {
+        events: Vec<RecordedEvent>,
+    }
+
+    impl Framework for Collect {
+        fn init(
+            _config: &wgpu::SurfaceConfiguration,
+            _targets: &RenderTargets,
+            _adapter: &wgpu::Adapter,
+            _device: &wgpu::Device,
+            _queue: &wgpu::Queue,
+        ) -> anyhow::Result<Self> {
+            Ok(Self::default())
+        }
+
+        fn render(
+            &mut self,
+            _ctx: &mut Context,
+            _encoder: &mut wgpu::CommandEncoder,
+            _view: &wgpu::TextureView,
+            _targets: &RenderTargets,
+            _device: &wgpu::Device,
+            _queue: &wgpu::Queue,
+        ) {
+        }
+
+        fn input(&mut self, device_id: DeviceId, event: DeviceEvent) {
+            assert_eq!(device_id, replay_device_id());
+            self.events.extend(RecordedEvent::from_device_event(&event));
+        }
+
+        fn on_event(&mut self, event: WindowEvent, _control_flow: &mut ControlFlow) {
+            self.events.extend(RecordedEvent::from_window_event(&event));
+        }
+    }
+
+    #[test]
+    fn replays_what_was_recorded() {
+        let recorded = [
+            key(VirtualKeyCode::D, ElementState::Pressed),
+            RecordedEvent::MouseMotion { x: 3.0, y: -1.0 },
+            RecordedEvent::CursorMoved { x: 10.0, y: 20.0 },
+        ];
+
+        let path = temp_path("round-trip");
+        let mut recorder = Recorder::create(&path, 60.0).unwrap();
+        recorder.record(WindowId::MAIN, recorded[0]);
+        recorder.record(WindowId::MAIN, recorded[1]);
+        recorder.frame(2);
+        recorder.record(WindowId::MAIN, recorded[2]);
+        // an event for a window that was closed by the time it's replayed
+        recorder.record(WindowId(7), RecordedEvent::CloseRequested);
+        recorder.frame(1);
+        drop(recorder);
+
+        let recording = Recording::load(&path).unwrap();
+        assert_eq!(recording.tick_hz, 60.0);
+        assert_eq!(recording.frames.len(), 2);
+
+        // saving it again loads the same
+        recording.save(&path).unwrap();
+        let reloaded = Recording::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(reloaded, recording);
+
+        let mut framework = Collect::default();
+        let mut ctx = Context::new(Time::new());
+        let mut control_flow = ControlFlow::Poll;
+        let mut replay = Replay::new(recording, 60.0);
+
+        assert_eq!(
+            replay.next_frame(&mut framework, &mut ctx, &mut control_flow),
+            Some(2)
+        );
+        assert!(ctx.input.key_held(VirtualKeyCode::D));
+        assert_eq!(ctx.input.mouse_delta(), glam::Vec2::new(3.0, -1.0));
+
+        assert_eq!(
+            replay.next_frame(&mut framework, &mut ctx, &mut control_flow),
+            Some(1)
+        );
+        assert_eq!(
+            replay.next_frame(&mut framework, &mut ctx, &mut control_flow),
+            None
+        );
+        assert_eq!(framework.events, recorded);
+    }
+
+    #[test]
+    fn loads_up_to_where_a_recording_was_cut_off() {
+        let frame = |ticks| RecordedFrame {
+            at: ticks as u64 * 1000,
+            ticks,
+            events: vec![TimedEvent {
+                at: 0,
+                window: WindowId::MAIN,
+                event: RecordedEvent::Focused(true),
+            }],
+        };
+        let recording = Recording {
+            tick_hz: 30.0,
+            frames: vec![frame(1), frame(2), frame(3)],
+        };
+
+        let path = temp_path("truncated");
+        recording.save(&path).unwrap();
+        let len = std::fs::metadata(&path).unwrap().len();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(len - 3)
+            .unwrap();
+
+        let loaded = Recording::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap().frames, recording.frames[..2]);
+    }
+
+    #[test]
+    fn rejects_oversized_frames() {
+        let path = temp_path("oversized");
+        let mut writer = create_recording(&path, 60.0).unwrap();
+
+        let event = bincode::serialize(&TimedEvent {
+            at: 0,
+            window: WindowId::MAIN,
+            event: RecordedEvent::CursorLeft,
+        })
+        .unwrap();
+        let count = MAX_FRAME_SIZE / event.len() as u64 + 1;
+
+        // a frame claiming far more events than the limit allows, followed by enough of them
+        // to reach it.
+        writer.write_all(&0u64.to_le_bytes()).unwrap();
+        writer.write_all(&1u32.to_le_bytes()).unwrap();
+        writer.write_all(&(1u64 << 40).to_le_bytes()).unwrap();
+        for _ in 0..count {
+            writer.write_all(&event).unwrap();
+        }
+        writer.flush().unwrap();
+        drop(writer);
+
+        let loaded = Recording::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(loaded.is_err());
+    }
+
+    #[test]
+    fn rejects_files_that_arent_recordings() {
+        let path = temp_path("not-a-recording");
+        std::fs::write(&path, b"PNG\0 definitely not a recording").unwrap();
+
+        let loaded = Recording::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(loaded.is_err());
+    }
+}
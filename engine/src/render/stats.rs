@@ -0,0 +1,207 @@
+use std::{collections::VecDeque, time::Duration};
+
+/// How many frames [`FrameStats`] keeps around.
+pub const FRAME_HISTORY: usize = 1000;
+
+/// The parts of a frame the CPU spends time on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Handling window and device events, including [`Framework::input`](super::framework::Framework::input)
+    /// and [`Framework::on_event`](super::framework::Framework::on_event), and replaying recorded ones.
+    Events,
+    /// [`Framework::update`](super::framework::Framework::update) and the fixed-step updates.
+    Update,
+    /// Processing the [`UploadQueue`](super::upload::UploadQueue).
+    Upload,
+    /// [`Framework::render`](super::framework::Framework::render).
+    Render,
+    Submit,
+    Present,
+}
+
+/// CPU time spent in each [`Phase`] of a single frame.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PhaseTimes {
+    pub events: Duration,
+    pub update: Duration,
+    pub upload: Duration,
+    pub render: Duration,
+    pub submit: Duration,
+    pub present: Duration,
+}
+
+/// Frame time history of the last [`FRAME_HISTORY`] frames.
+#[derive(Debug, Clone)]
+pub struct FrameStats {
+    history: VecDeque<Duration>,
+    jitter: VecDeque<Duration>,
+    current: PhaseTimes,
+    last: PhaseTimes,
+    frames: u64,
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self {
+            history: VecDeque::with_capacity(FRAME_HISTORY),
+            jitter: VecDeque::new(),
+            current: PhaseTimes::default(),
+            last: PhaseTimes::default(),
+            frames: 0,
+        }
+    }
+}
+
+impl FrameStats {
+    pub fn record_phase(&mut self, phase: Phase, duration: Duration) {
+        let time = match phase {
+            Phase::Events => &mut self.current.events,
+            Phase::Update => &mut self.current.update,
+            Phase::Upload => &mut self.current.upload,
+            Phase::Render => &mut self.current.render,
+            Phase::Submit => &mut self.current.submit,
+            Phase::Present => &mut self.current.present,
+        };
+
+        *time += duration;
+    }
+
+    /// Finishes the current frame, which took `frame_time` in total.
+    pub fn record_frame(&mut self, frame_time: Duration) {
+        if self.history.len() == FRAME_HISTORY {
+            self.history.pop_front();
+        }
+
+        self.history.push_back(frame_time);
+        self.last = std::mem::take(&mut self.current);
+        self.frames += 1;
+    }
+
+    /// Records how far the time between two frames was off from the framerate limit.
+    pub fn record_jitter(&mut self, jitter: Duration) {
+        if self.jitter.len() == FRAME_HISTORY {
+            self.jitter.pop_front();
+        }
+
+        self.jitter.push_back(jitter);
+    }
+
+    /// Average pacing jitter of the last [`FRAME_HISTORY`] frames run under a framerate limit.
+    pub fn jitter(&self) -> Duration {
+        match self.jitter.len() {
+            0 => Duration::ZERO,
+            len => self.jitter.iter().sum::<Duration>() / len as u32,
+        }
+    }
+
+    pub fn max_jitter(&self) -> Duration {
+        self.jitter.iter().max().copied().unwrap_or_default()
+    }
+
+    /// Total amount of frames recorded, including the ones no longer in the history.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    pub fn history(&self) -> impl Iterator<Item = Duration> + '_ {
+        self.history.iter().copied()
+    }
+
+    /// Phase times of the last finished frame.
+    pub fn phases(&self) -> PhaseTimes {
+        self.last
+    }
+
+    pub fn last_frame_time(&self) -> Duration {
+        self.history.back().copied().unwrap_or_default()
+    }
+
+    pub fn min(&self) -> Duration {
+        self.history.iter().min().copied().unwrap_or_default()
+    }
+
+    pub fn max(&self) -> Duration {
+        self.history.iter().max().copied().unwrap_or_default()
+    }
+
+    pub fn avg(&self) -> Duration {
+        match self.history.len() {
+            0 => Duration::ZERO,
+            len => self.history.iter().sum::<Duration>() / len as u32,
+        }
+    }
+
+    pub fn fps(&self) -> f64 {
+        to_fps(self.avg())
+    }
+
+    /// Average frame time of the slowest 1% of frames.
+    pub fn low_1_percent(&self) -> Duration {
+        self.slowest_average(0.01)
+    }
+
+    /// Average frame time of the slowest 0.1% of frames.
+    pub fn low_0_1_percent(&self) -> Duration {
+        self.slowest_average(0.001)
+    }
+
+    /// A short, human readable summary like `"143.2 fps (6.98ms, 1% low 98.1 fps, 0.1% low 61.0 fps)"`.
+    /// Under a framerate limit, the pacing jitter is included as well.
+    pub fn summary(&self) -> String {
+        let summary = format!(
+            "{:.1} fps ({:.2}ms, 1% low {:.1} fps, 0.1% low {:.1} fps)",
+            self.fps(),
+            self.avg().as_secs_f64() * 1000.0,
+            to_fps(self.low_1_percent()),
+            to_fps(self.low_0_1_percent()),
+        );
+
+        match self.jitter.is_empty() {
+            true => summary,
+            false => format!(
+                "{summary} jitter {:.3}ms",
+                self.jitter().as_secs_f64() * 1000.0
+            ),
+        }
+    }
+
+    fn slowest_average(&self, fraction: f64) -> Duration {
+        if self.history.is_empty() {
+            return Duration::ZERO;
+        }
+
+        let mut sorted = self.history.iter().copied().collect::<Vec<_>>();
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+
+        let count = ((sorted.len() as f64 * fraction).ceil() as usize).max(1);
+        sorted[..count].iter().sum::<Duration>() / count as u32
+    }
+}
+
+fn to_fps(frame_time: Duration) -> f64 {
+    match frame_time.is_zero() {
+        true => 0.0,
+        false => 1.0 / frame_time.as_secs_f64(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_are_kept_apart() {
+        let mut stats = FrameStats::default();
+        stats.record_phase(Phase::Update, Duration::from_millis(3));
+        stats.record_phase(Phase::Upload, Duration::from_millis(2));
+        stats.record_phase(Phase::Render, Duration::from_millis(1));
+        stats.record_phase(Phase::Render, Duration::from_millis(1));
+        stats.record_frame(Duration::from_millis(10));
+
+        let phases = stats.phases();
+        assert_eq!(phases.update, Duration::from_millis(3));
+        assert_eq!(phases.upload, Duration::from_millis(2));
+        assert_eq!(phases.render, Duration::from_millis(2));
+        assert_eq!(phases.events, Duration::ZERO);
+    }
+}
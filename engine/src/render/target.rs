@@ -0,0 +1,212 @@
+use wgpu::{Color, RenderPassColorAttachment, RenderPassDepthStencilAttachment, TextureView};
+
+use super::{builder::pass::RenderPassColorAttachmentBuilder, color, error::EngineError};
+
+/// Depth and multisampled color textures owned by the framework, which follow the surface size.
+pub struct RenderTargets {
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    depth_format: Option<wgpu::TextureFormat>,
+    depth: Option<Target>,
+    msaa: Option<Target>,
+}
+
+struct Target {
+    _texture: wgpu::Texture,
+    view: wgpu::TextureView,
+}
+
+impl RenderTargets {
+    /// Creates the targets for `config`. A depth texture is only created if `depth_format` is set,
+    /// and a multisampled color texture only if `sample_count` is above 1.
+    ///
+    /// Fails if the color or depth format can't be multisampled `sample_count` times.
+    pub fn new(
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        sample_count: u32,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Result<Self, EngineError> {
+        let sample_count = sample_count.max(1);
+
+        for format in std::iter::once(config.format).chain(depth_format) {
+            let flags = format_flags(adapter, device, format);
+            // the color target also has to be resolved into the surface
+            let resolvable = format != config.format
+                || sample_count == 1
+                || flags.contains(wgpu::TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE);
+
+            if !flags.sample_count_supported(sample_count) || !resolvable {
+                return Err(EngineError::UnsupportedSampleCount(sample_count, format));
+            }
+        }
+
+        let mut targets = Self {
+            format: config.format,
+            sample_count,
+            depth_format,
+            depth: None,
+            msaa: None,
+        };
+
+        targets.resize(device, config);
+        Ok(targets)
+    }
+
+    /// Recreates every target at the size of `config`.
+    pub fn resize(&mut self, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) {
+        self.format = config.format;
+        self.depth = self
+            .depth_format
+            .map(|format| Target::new(device, config, format, self.sample_count, "depth target"));
+
+        self.msaa = (self.sample_count > 1).then(|| {
+            Target::new(
+                device,
+                config,
+                config.format,
+                self.sample_count,
+                "multisampled color target",
+            )
+        });
+    }
+
+    /// Format of the surface the targets were made for.
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    /// `color` converted for the surface, to clear it with.
+    pub fn clear_color(&self, color: color::Color) -> Color {
+        color.wgpu_for(self.format)
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn depth_format(&self) -> Option<wgpu::TextureFormat> {
+        self.depth_format
+    }
+
+    pub fn depth_view(&self) -> Option<&TextureView> {
+        self.depth.as_ref().map(|target| &target.view)
+    }
+
+    pub fn msaa_view(&self) -> Option<&TextureView> {
+        self.msaa.as_ref().map(|target| &target.view)
+    }
+
+    /// A color attachment that ends up in `view`. With multisampling, this renders into the
+    /// multisampled target and resolves into `view`.
+    pub fn color_attachment<'a>(
+        &'a self,
+        view: &'a TextureView,
+        ops: wgpu::Operations<Color>,
+    ) -> Option<RenderPassColorAttachment<'a>> {
+        match self.msaa_view() {
+            Some(msaa_view) => RenderPassColorAttachmentBuilder::builder()
+                .resolve_target(view)
+                .ops(ops)
+                .build()
+                .attach_opt(msaa_view),
+            None => RenderPassColorAttachmentBuilder::builder()
+                .ops(ops)
+                .build()
+                .attach_opt(view),
+        }
+    }
+
+    /// An attachment for the depth target, if there is one.
+    pub fn depth_attachment(
+        &self,
+        depth_ops: wgpu::Operations<f32>,
+    ) -> Option<RenderPassDepthStencilAttachment<'_>> {
+        self.depth_view()
+            .map(|view| RenderPassDepthStencilAttachment {
+                view,
+                depth_ops: Some(depth_ops),
+                stencil_ops: None,
+            })
+    }
+}
+
+/// The features the device may use `format` with. Without
+/// [`wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES`], that's only what every adapter guarantees.
+fn format_flags(
+    adapter: &wgpu::Adapter,
+    device: &wgpu::Device,
+    format: wgpu::TextureFormat,
+) -> wgpu::TextureFormatFeatureFlags {
+    if device
+        .features()
+        .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+    {
+        adapter.get_texture_format_features(format).flags
+    } else {
+        format.describe().guaranteed_format_features.flags
+    }
+}
+
+impl Target {
+    fn new(
+        device: &wgpu::Device,
+        config: &wgpu::SurfaceConfiguration,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        label: &str,
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+
+        Self {
+            view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            _texture: texture,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{config::EngineConfig, headless};
+
+    #[test]
+    fn rejects_unsupported_sample_counts() {
+        let (_instance, adapter, device, _queue) =
+            pollster::block_on(headless::init_headless_wgpu(&EngineConfig::default())).unwrap();
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width: 4,
+            height: 4,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            view_formats: vec![],
+        };
+        let depth = Some(wgpu::TextureFormat::Depth32Float);
+
+        assert!(RenderTargets::new(&adapter, &device, &config, 1, depth).is_ok());
+        assert!(matches!(
+            RenderTargets::new(&adapter, &device, &config, 3, depth),
+            Err(EngineError::UnsupportedSampleCount(3, _))
+        ));
+        assert!(matches!(
+            RenderTargets::new(&adapter, &device, &config, 64, None),
+            Err(EngineError::UnsupportedSampleCount(64, _))
+        ));
+    }
+}
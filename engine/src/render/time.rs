@@ -1,18 +1,75 @@
 use std::time::{Duration, Instant};
 
+/// How often [`Framework::update`](super::framework::Framework::update) runs, independent of the framerate.
+#[derive(Debug, Clone, Copy)]
+pub struct TickRate {
+    pub hz: f64,
+    /// The most ticks a single frame may catch up on. Anything past this is dropped, so a slow
+    /// frame can't cause ever slower frames.
+    pub max_steps: u32,
+}
+
+impl TickRate {
+    /// Whether the rate is positive, and at most one tick per nanosecond so a step is never zero.
+    pub fn is_valid(&self) -> bool {
+        self.hz > 0.0 && self.hz <= 1e9
+    }
+
+    pub fn step(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.hz)
+    }
+}
+
+impl Default for TickRate {
+    fn default() -> Self {
+        Self {
+            hz: 60.0,
+            max_steps: 8,
+        }
+    }
+}
+
 pub struct Time {
     last_time: Instant,
+    /// When the accumulator was last advanced, which also counts the time spent rendering.
+    last_accumulate: Instant,
+    fixed_delta: Option<Duration>,
+    accumulator: Duration,
+    alpha: f64,
+}
+
+impl Default for Time {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Time {
     pub fn new() -> Self {
+        let now = Instant::now();
+
+        Self {
+            last_time: now,
+            last_accumulate: now,
+            fixed_delta: None,
+            accumulator: Duration::ZERO,
+            alpha: 0.0,
+        }
+    }
+
+    /// A deterministic clock, which reports the same `delta` every frame no matter how long frames actually take.
+    pub fn fixed(delta: Duration) -> Self {
         Self {
-            last_time: Instant::now(),
+            fixed_delta: Some(delta),
+            ..Self::new()
         }
     }
 
     pub fn time_delta(&self) -> Duration {
-        Instant::now().duration_since(self.last_time)
+        match self.fixed_delta {
+            Some(delta) => delta,
+            None => Instant::now().duration_since(self.last_time),
+        }
     }
 
     pub fn delta_seconds_f64(&self) -> f64 {
@@ -23,6 +80,60 @@ impl Time {
         self.time_delta().as_secs_f32()
     }
 
+    /// How far the current frame is between the last tick and the next one, in `0.0..1.0`.
+    ///
+    /// Blending the previous and current simulation state by this avoids stutter when the
+    /// framerate and tick rate don't line up.
+    pub fn interpolation_alpha(&self) -> f64 {
+        self.alpha
+    }
+
+    pub fn interpolation_alpha_f32(&self) -> f32 {
+        self.alpha as f32
+    }
+
+    /// Adds the time since the last call to the fixed-step accumulator, and returns how many
+    /// ticks of `rate` are due.
+    pub fn accumulate(&mut self, rate: &TickRate) -> u32 {
+        let step = rate.step();
+        let now = Instant::now();
+
+        self.accumulator += match self.fixed_delta {
+            Some(delta) => delta,
+            None => now.duration_since(self.last_accumulate),
+        };
+        self.last_accumulate = now;
+
+        let mut steps = 0;
+
+        while self.accumulator >= step {
+            if steps == rate.max_steps {
+                // we're too far behind to ever catch up, so forget about the ticks we couldn't run.
+                self.accumulator =
+                    Duration::from_secs_f64(self.accumulator.as_secs_f64() % step.as_secs_f64());
+                break;
+            }
+
+            self.accumulator -= step;
+            steps += 1;
+        }
+
+        self.alpha = self.accumulator.as_secs_f64() / step.as_secs_f64();
+        steps
+    }
+
+    /// Switches between a deterministic clock reporting `delta`, and the wall clock.
+    pub(crate) fn set_fixed_delta(&mut self, delta: Option<Duration>) {
+        self.fixed_delta = delta;
+        self.resume();
+    }
+
+    /// Restarts the clock after a pause, so the pause isn't counted as frame time.
+    pub(crate) fn resume(&mut self) {
+        self.last_time = Instant::now();
+        self.last_accumulate = self.last_time;
+    }
+
     pub fn post_update(&mut self) {
         self.last_time = Instant::now();
     }
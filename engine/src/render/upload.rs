@@ -0,0 +1,360 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use super::{
+    jobs::{JobError, JobHandle},
+    mesh::{Mesh, RawMesh},
+};
+
+/// CPU-side data that ends up in GPU resources, like a [`Mesh`] becoming a [`RawMesh`].
+pub trait Upload: Send + 'static {
+    type Output: 'static;
+
+    /// Roughly how many bytes uploading this writes, counted against [`UploadBudget::bytes`].
+    fn size(&self) -> u64;
+
+    fn upload(self, device: &wgpu::Device, queue: &wgpu::Queue) -> Self::Output;
+}
+
+impl Upload for Mesh {
+    type Output = RawMesh;
+
+    fn size(&self) -> u64 {
+        let vertices = std::mem::size_of_val(self.vertices.as_slice());
+        let indices = self
+            .indices
+            .as_ref()
+            .map_or(0, |indices| std::mem::size_of_val(indices.as_slice()));
+
+        (vertices + indices) as u64
+    }
+
+    fn upload(self, device: &wgpu::Device, _queue: &wgpu::Queue) -> RawMesh {
+        self.to_raw(device)
+    }
+}
+
+/// How much an [`UploadQueue`] may upload per frame. At least one upload goes through every
+/// frame, so nothing can be held back forever by being larger than the budget.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct UploadBudget {
+    pub time: Option<Duration>,
+    pub bytes: Option<u64>,
+}
+
+impl UploadBudget {
+    /// Whether another upload of `bytes` fits in the frame, after the uploads in `stats` took
+    /// `elapsed`.
+    fn fits(&self, stats: &UploadStats, bytes: u64, elapsed: Duration) -> bool {
+        let over_time = matches!(self.time, Some(time) if elapsed >= time);
+        let over_bytes = matches!(self.bytes, Some(budget) if stats.bytes + bytes > budget);
+
+        stats.uploaded == 0 || !(over_time || over_bytes)
+    }
+}
+
+/// What an [`UploadQueue`] got done in a frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UploadStats {
+    pub uploaded: usize,
+    pub bytes: u64,
+    pub time: Duration,
+    /// Uploads still waiting for their job or the next frame.
+    pub remaining: usize,
+}
+
+enum Slot<T> {
+    Pending,
+    Uploaded(T),
+    Failed(JobError),
+    Taken,
+}
+
+/// A GPU resource that is uploaded once its job finishes, see [`UploadQueue`].
+///
+/// Dropping the handle cancels the job, and the upload with it.
+pub struct UploadHandle<T> {
+    slot: Rc<RefCell<Slot<T>>>,
+}
+
+impl<T> UploadHandle<T> {
+    pub fn is_ready(&self) -> bool {
+        !matches!(*self.slot.borrow(), Slot::Pending)
+    }
+
+    /// Takes the uploaded resource, or the reason there is none. Only the first call after the
+    /// upload returns it.
+    pub fn take(&mut self) -> Option<Result<T, JobError>> {
+        let mut slot = self.slot.borrow_mut();
+
+        match std::mem::replace(&mut *slot, Slot::Taken) {
+            Slot::Uploaded(output) => Some(Ok(output)),
+            Slot::Failed(err) => Some(Err(err)),
+            pending => {
+                *slot = pending;
+                None
+            }
+        }
+    }
+}
+
+enum Poll {
+    Waiting,
+    Ready { bytes: u64 },
+    Done,
+}
+
+trait PendingUpload {
+    fn poll(&mut self) -> Poll;
+
+    fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue);
+}
+
+struct TypedUpload<T: Upload> {
+    job: JobHandle<T>,
+    ready: Option<T>,
+    slot: Rc<RefCell<Slot<T::Output>>>,
+}
+
+impl<T: Upload> PendingUpload for TypedUpload<T> {
+    fn poll(&mut self) -> Poll {
+        // nobody is waiting for it anymore.
+        if Rc::strong_count(&self.slot) == 1 {
+            self.job.cancel();
+            return Poll::Done;
+        }
+
+        if self.ready.is_none() {
+            match self.job.try_take() {
+                Some(Ok(data)) => self.ready = Some(data),
+                Some(Err(err)) => {
+                    *self.slot.borrow_mut() = Slot::Failed(err);
+                    return Poll::Done;
+                }
+                None => return Poll::Waiting,
+            }
+        }
+
+        match &self.ready {
+            Some(data) => Poll::Ready { bytes: data.size() },
+            None => Poll::Waiting,
+        }
+    }
+
+    fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        if let Some(data) = self.ready.take() {
+            *self.slot.borrow_mut() = Slot::Uploaded(data.upload(device, queue));
+        }
+    }
+}
+
+/// Results of jobs waiting to be uploaded on the main thread, in the order they were pushed.
+///
+/// The framework runners call [`UploadQueue::process`] once per frame, before rendering.
+#[derive(Default)]
+pub struct UploadQueue {
+    pending: VecDeque<Box<dyn PendingUpload>>,
+    budget: UploadBudget,
+    last: UploadStats,
+}
+
+impl UploadQueue {
+    pub fn new(budget: UploadBudget) -> Self {
+        Self {
+            budget,
+            ..Default::default()
+        }
+    }
+
+    pub fn push<T: Upload>(&mut self, job: JobHandle<T>) -> UploadHandle<T::Output> {
+        let slot = Rc::new(RefCell::new(Slot::Pending));
+
+        self.pending.push_back(Box::new(TypedUpload {
+            job,
+            ready: None,
+            slot: slot.clone(),
+        }));
+
+        UploadHandle { slot }
+    }
+
+    pub fn budget(&self) -> UploadBudget {
+        self.budget
+    }
+
+    pub fn set_budget(&mut self, budget: UploadBudget) {
+        self.budget = budget;
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// What the last call to [`UploadQueue::process`] got done.
+    pub fn last_stats(&self) -> UploadStats {
+        self.last
+    }
+
+    /// Uploads finished jobs until the budget runs out. Uploads whose job hasn't finished yet
+    /// don't hold back the ones after them.
+    pub fn process(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> UploadStats {
+        let start = Instant::now();
+        let mut stats = UploadStats::default();
+        let mut idx = 0;
+
+        while idx < self.pending.len() {
+            let bytes = match self.pending[idx].poll() {
+                Poll::Waiting => {
+                    idx += 1;
+                    continue;
+                }
+                Poll::Done => {
+                    self.pending.remove(idx);
+                    continue;
+                }
+                Poll::Ready { bytes } => bytes,
+            };
+
+            if !self.budget.fits(&stats, bytes, start.elapsed()) {
+                break;
+            }
+
+            if let Some(mut upload) = self.pending.remove(idx) {
+                upload.upload(device, queue);
+            }
+
+            stats.uploaded += 1;
+            stats.bytes += bytes;
+        }
+
+        stats.time = start.elapsed();
+        stats.remaining = self.pending.len();
+        self.last = stats;
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::{
+        config::EngineConfig,
+        headless,
+        jobs::{JobSystem, Priority},
+    };
+
+    const MS: Duration = Duration::from_millis(1);
+
+    fn after(uploaded: usize, bytes: u64) -> UploadStats {
+        UploadStats {
+            uploaded,
+            bytes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn budget_limits_bytes_and_time() {
+        let budget = UploadBudget {
+            time: Some(MS * 2),
+            bytes: Some(100),
+        };
+
+        assert!(budget.fits(&after(1, 60), 40, MS));
+        assert!(!budget.fits(&after(1, 60), 41, MS));
+        assert!(!budget.fits(&after(1, 10), 10, MS * 2));
+    }
+
+    #[test]
+    fn budget_lets_the_first_upload_through() {
+        let budget = UploadBudget {
+            time: Some(Duration::ZERO),
+            bytes: Some(10),
+        };
+
+        assert!(budget.fits(&after(0, 0), 1000, MS));
+        assert!(!budget.fits(&after(1, 0), 1, Duration::ZERO));
+    }
+
+    #[test]
+    fn no_budget_fits_everything() {
+        let budget = UploadBudget::default();
+        assert!(budget.fits(&after(1000, u64::MAX / 2), u64::MAX / 2, Duration::MAX));
+    }
+
+    struct Blob(u64);
+
+    impl Upload for Blob {
+        type Output = u64;
+
+        fn size(&self) -> u64 {
+            self.0
+        }
+
+        fn upload(self, _device: &wgpu::Device, _queue: &wgpu::Queue) -> u64 {
+            self.0
+        }
+    }
+
+    fn finished<T: Send + 'static>(jobs: &JobSystem, output: T) -> JobHandle<T> {
+        let job = jobs.spawn(Priority::Normal, move |_| output);
+
+        while !job.is_finished() {
+            std::thread::yield_now();
+        }
+
+        job
+    }
+
+    fn stats(stats: UploadStats) -> (usize, u64, usize) {
+        (stats.uploaded, stats.bytes, stats.remaining)
+    }
+
+    #[test]
+    fn uploads_in_order_within_the_budget() {
+        let (_instance, _adapter, device, queue) =
+            pollster::block_on(headless::init_headless_wgpu(&EngineConfig::default())).unwrap();
+        let jobs = JobSystem::new(2);
+        let mut uploads = UploadQueue::new(UploadBudget {
+            time: None,
+            bytes: Some(100),
+        });
+
+        let (release, released) = std::sync::mpsc::channel::<()>();
+        let mut waiting = uploads.push(jobs.spawn(Priority::Normal, move |_| {
+            released.recv().unwrap();
+            Blob(5)
+        }));
+        let mut handles = [60, 30, 20, 200].map(|size| uploads.push(finished(&jobs, Blob(size))));
+        drop(uploads.push(finished(&jobs, Blob(1))));
+
+        // the unfinished job doesn't hold back the ones after it.
+        assert_eq!(stats(uploads.process(&device, &queue)), (2, 90, 4));
+        assert_eq!(handles[0].take(), Some(Ok(60)));
+        assert_eq!(handles[1].take(), Some(Ok(30)));
+        assert!(!handles[2].is_ready());
+
+        // 200 bytes are over the budget, but go through as the only upload of a frame, and the
+        // dropped upload is skipped.
+        assert_eq!(stats(uploads.process(&device, &queue)), (1, 20, 3));
+        assert_eq!(stats(uploads.process(&device, &queue)), (1, 200, 1));
+        assert_eq!(handles[3].take(), Some(Ok(200)));
+
+        release.send(()).unwrap();
+        while !waiting.is_ready() {
+            uploads.process(&device, &queue);
+        }
+
+        assert_eq!(waiting.take(), Some(Ok(5)));
+        assert!(uploads.is_empty());
+    }
+}
@@ -25,10 +25,12 @@ impl VertexDescriptor<2> for Vertex {
     }
 }
 
-#[derive(TypedBuilder, Debug)]
+#[derive(TypedBuilder, Debug, Clone)]
 pub struct Transform {
     pub translation: Vec3,
     pub rotation: Quat,
+    #[builder(default = Vec3::ONE)]
+    pub scale: Vec3,
 }
 
 #[repr(C)]
@@ -39,8 +41,12 @@ pub struct TransformRaw {
 }
 
 impl Transform {
-    pub fn to_raw<'a>(&self, _params: &'a RawParams) -> TransformRaw {
-        let model = Mat4::from_translation(self.translation) * Mat4::from_quat(self.rotation);
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+
+    pub fn to_raw(&self, _params: &RawParams) -> TransformRaw {
+        let model = self.matrix();
         let normal_matrix = model.inverse().transpose();
 
         TransformRaw {
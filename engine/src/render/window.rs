@@ -0,0 +1,245 @@
+use serde::{Deserialize, Serialize};
+use typed_builder::TypedBuilder;
+use winit::{
+    dpi::{PhysicalSize, Size},
+    window::{CursorGrabMode, Fullscreen, Window},
+};
+
+/// Identifies one of the windows of a [`Framework`](super::framework::Framework).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct WindowId(pub(crate) u64);
+
+impl WindowId {
+    /// The window the framework was started in, closing it exits the event loop.
+    pub const MAIN: WindowId = WindowId(0);
+}
+
+/// Describes a window opened with [`Context::open_window`](super::context::Context::open_window).
+#[derive(TypedBuilder, Debug, Clone)]
+pub struct WindowOptions {
+    #[builder(setter(into))]
+    pub title: String,
+    #[builder(default = 800)]
+    pub width: u32,
+    #[builder(default = 600)]
+    pub height: u32,
+}
+
+/// How the cursor is kept inside the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorGrab {
+    None,
+    /// The cursor can move, but not leave the window.
+    Confined,
+    /// The cursor stays where it is, which suits mouse-look.
+    Locked,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FullscreenMode {
+    Windowed,
+    /// A window covering the current monitor.
+    Borderless,
+    /// Takes over the current monitor, at its largest and fastest video mode.
+    Exclusive,
+}
+
+/// Control over the window a [`Framework`](super::framework::Framework) runs in.
+///
+/// Headless runs have no window, so everything but [`WindowHandle::request_exit`] does nothing there.
+#[derive(Default)]
+pub struct WindowHandle {
+    window: Option<Window>,
+    title: String,
+    cursor_grab: Option<CursorGrab>,
+    cursor_visible: bool,
+    focused: bool,
+    minimized: bool,
+    occluded: bool,
+    exit_requested: bool,
+}
+
+impl WindowHandle {
+    pub(crate) fn new(window: Window, title: String) -> Self {
+        Self {
+            window: Some(window),
+            title,
+            cursor_grab: None,
+            cursor_visible: true,
+            focused: true,
+            minimized: false,
+            occluded: false,
+            exit_requested: false,
+        }
+    }
+
+    pub(crate) fn headless() -> Self {
+        Self {
+            cursor_visible: true,
+            focused: true,
+            ..Default::default()
+        }
+    }
+
+    /// The underlying window, if there is one.
+    pub fn winit(&self) -> Option<&Window> {
+        self.window.as_ref()
+    }
+
+    /// Grabs the cursor, falling back to the other grab mode if the platform only supports one of them.
+    pub fn set_cursor_grab(&mut self, grab: CursorGrab) {
+        let Some(window) = &self.window else {
+            return;
+        };
+
+        let modes = match grab {
+            CursorGrab::None => &[CursorGrabMode::None][..],
+            // windows and x11 can only confine, macos can only lock.
+            CursorGrab::Confined => &[CursorGrabMode::Confined, CursorGrabMode::Locked],
+            CursorGrab::Locked => &[CursorGrabMode::Locked, CursorGrabMode::Confined],
+        };
+
+        self.cursor_grab = modes.iter().find_map(|mode| {
+            window.set_cursor_grab(*mode).ok().map(|_| match mode {
+                CursorGrabMode::None => CursorGrab::None,
+                CursorGrabMode::Confined => CursorGrab::Confined,
+                CursorGrabMode::Locked => CursorGrab::Locked,
+            })
+        });
+
+        if self.cursor_grab.is_none() {
+            log::warn!("unable to grab the cursor with {grab:?}");
+        }
+    }
+
+    /// The grab mode that is actually in effect, which may differ from the requested one.
+    pub fn cursor_grab(&self) -> CursorGrab {
+        self.cursor_grab.unwrap_or(CursorGrab::None)
+    }
+
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        if let Some(window) = &self.window {
+            window.set_cursor_visible(visible);
+            self.cursor_visible = visible;
+        }
+    }
+
+    pub fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    pub fn set_fullscreen(&mut self, mode: FullscreenMode) {
+        let Some(window) = &self.window else {
+            return;
+        };
+
+        let fullscreen = match mode {
+            FullscreenMode::Windowed => None,
+            FullscreenMode::Borderless => Some(Fullscreen::Borderless(window.current_monitor())),
+            FullscreenMode::Exclusive => {
+                let video_mode = window.current_monitor().and_then(|monitor| {
+                    monitor.video_modes().max_by_key(|video_mode| {
+                        let size = video_mode.size();
+                        (
+                            size.width * size.height,
+                            video_mode.refresh_rate_millihertz(),
+                        )
+                    })
+                });
+
+                match video_mode {
+                    Some(video_mode) => Some(Fullscreen::Exclusive(video_mode)),
+                    None => {
+                        log::warn!("no video mode for exclusive fullscreen, using borderless");
+                        Some(Fullscreen::Borderless(None))
+                    }
+                }
+            }
+        };
+
+        window.set_fullscreen(fullscreen);
+    }
+
+    pub fn fullscreen(&self) -> FullscreenMode {
+        match self.window.as_ref().and_then(Window::fullscreen) {
+            None => FullscreenMode::Windowed,
+            Some(Fullscreen::Borderless(_)) => FullscreenMode::Borderless,
+            Some(Fullscreen::Exclusive(_)) => FullscreenMode::Exclusive,
+        }
+    }
+
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.title = title.into();
+
+        if let Some(window) = &self.window {
+            window.set_title(&self.title);
+        }
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Size of the window's contents in physical pixels, or zero without a window.
+    pub fn inner_size(&self) -> PhysicalSize<u32> {
+        self.window
+            .as_ref()
+            .map(Window::inner_size)
+            .unwrap_or_default()
+    }
+
+    /// Resizes the window's contents, the new size arrives through [`Framework::resize`](super::framework::Framework::resize).
+    pub fn set_inner_size(&mut self, size: impl Into<Size>) {
+        if let Some(window) = &self.window {
+            window.set_inner_size(size);
+        }
+    }
+
+    pub fn scale_factor(&self) -> f64 {
+        self.window
+            .as_ref()
+            .map(Window::scale_factor)
+            .unwrap_or(1.0)
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Whether the window is minimized, in which case nothing is rendered.
+    pub fn is_minimized(&self) -> bool {
+        self.minimized
+    }
+
+    /// Whether the window is entirely hidden behind other windows, as far as the platform reports it.
+    pub fn is_occluded(&self) -> bool {
+        self.occluded
+    }
+
+    pub(crate) fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    pub(crate) fn set_minimized(&mut self, minimized: bool) {
+        self.minimized = minimized;
+    }
+
+    pub(crate) fn set_occluded(&mut self, occluded: bool) {
+        self.occluded = occluded;
+    }
+
+    /// Stops the event loop, or a headless run, after the current frame.
+    pub fn request_exit(&mut self) {
+        self.exit_requested = true;
+    }
+
+    pub fn exit_requested(&self) -> bool {
+        self.exit_requested
+    }
+
+    pub(crate) fn request_redraw(&self) {
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+    }
+}
@@ -1,23 +1,31 @@
 use engine::render::{
     self,
-    builder::pass::{RenderPassBuilder, RenderPassColorAttachmentBuilder},
+    builder::pass::RenderPassBuilder,
     bundle::mesh::{Bundles, MeshBundle},
     camera::{Camera, CameraBind, CameraPerspective, CameraRender},
     color::Color,
-    framework::{EventLoop, Framework},
+    config::EngineConfig,
+    context::Context,
+    framework::Framework,
+    input::{ActionMap, AxisBinding, Binding},
     material::color::StaticColorMaterial,
     mesh::Mesh,
     raw::{RawBindingRender, RawParams},
+    stats::FrameStats,
+    target::RenderTargets,
     vertex::{Transform, Vertex},
 };
 
+use std::time::Duration;
+
 use glam::{Quat, Vec3, Vec4};
-use winit::window::Window;
+use winit::event::VirtualKeyCode;
+
+fn main() -> anyhow::Result<()> {
+    let config = EngineConfig::load_or_default("engine.toml")?;
+    render::framework::run::<VoxelFramework>(config)?;
 
-fn main() {
-    let event_loop = EventLoop::new();
-    let window = Window::new(&event_loop).unwrap();
-    render::framework::run::<VoxelFramework>(window, event_loop);
+    Ok(())
 }
 
 pub struct VoxelFramework {
@@ -38,10 +46,11 @@ const VERTICES: &[([f32; 3], [f32; 3])] = &[
 impl Framework for VoxelFramework {
     fn init(
         config: &wgpu::SurfaceConfiguration,
+        targets: &RenderTargets,
         _: &wgpu::Adapter,
         device: &wgpu::Device,
         _: &wgpu::Queue,
-    ) -> Self {
+    ) -> anyhow::Result<Self> {
         let mut bundles = Bundles::<StaticColorMaterial>::default();
         let tri_mesh = Mesh::builder()
             .vertices(
@@ -73,11 +82,9 @@ impl Framework for VoxelFramework {
         let raw_bind_camera =
             bind_camera.create_raw_bind(device, bytemuck::cast_slice(&[bind_camera]));
 
-        let params: RawParams = (device, config, &raw_bind_camera).into();
+        let params: RawParams = (device, config, &raw_bind_camera, targets).into();
         let tri_mat = StaticColorMaterial::builder()
-            .color(<[f32; 4] as Into<engine::render::color::Color>>::into([
-                1.0, 0.0, 1.0, 0.3,
-            ]))
+            .color(Color::srgb(1.0, 0.0, 1.0, 0.3))
             .build();
 
         let mesh_bundle = MeshBundle::builder()
@@ -99,46 +106,98 @@ impl Framework for VoxelFramework {
 
         bundles.process_queue(&params);
 
-        Self {
+        Ok(Self {
             camera,
             bind_camera,
             raw_bind_camera,
             bundles,
-        }
+        })
+    }
+
+    fn update(&mut self, ctx: &mut Context, fixed_dt: Duration) {
+        // the camera keeps drifting on its own, the move_x axis speeds it up or holds it back.
+        let speed = 5.0 + 5.0 * ctx.input().axis("move_x");
+        self.camera.eye.x += speed * fixed_dt.as_secs_f32();
+    }
+
+    fn action_map(&self) -> ActionMap {
+        let mut actions = ActionMap::default();
+        actions.bind_axis(
+            "move_x",
+            AxisBinding::Buttons {
+                positive: Binding::Key(VirtualKeyCode::D),
+                negative: Binding::Key(VirtualKeyCode::A),
+            },
+        );
+
+        ActionMap::load_or("input.toml", actions.clone()).unwrap_or_else(|err| {
+            log::error!("unable to load input.toml, using the default bindings: {err:?}");
+            actions
+        })
+    }
+
+    fn window_title(&self, stats: &FrameStats) -> Option<String> {
+        Some(format!("Voxel [{}]", stats.summary()))
     }
 
     fn render(
         &mut self,
-        time: &render::time::Time,
+        ctx: &mut Context,
         encoder: &mut wgpu::CommandEncoder,
         view: &wgpu::TextureView,
+        targets: &RenderTargets,
         _: &wgpu::Device,
         queue: &wgpu::Queue,
     ) {
-        let clear_attachment = RenderPassColorAttachmentBuilder::builder()
-            .ops(wgpu::Operations {
-                load: wgpu::LoadOp::Clear(Color::from([0.5, 0.2, 0.3, 1.0]).wgpu()),
-                store: true,
-            })
-            .build()
-            .attach_opt(view);
+        if ctx.input().key_pressed(VirtualKeyCode::Escape) {
+            ctx.window_mut().request_exit();
+        }
 
-        let tri_attachments = [clear_attachment];
-        let mut render_pass = RenderPassBuilder::builder()
-            .label("voxel pass")
-            .color_attachments(&tri_attachments)
-            .build()
-            .begin(encoder);
+        let clear_attachment = targets.color_attachment(
+            view,
+            wgpu::Operations {
+                load: wgpu::LoadOp::Clear(targets.clear_color(Color::srgb(0.5, 0.2, 0.3, 1.0))),
+                store: true,
+            },
+        );
 
-        self.camera.eye.x += 5.0 * time.delta_seconds_f32();
         self.bind_camera.update_view_proj(&self.camera);
         self.raw_bind_camera
             .update_buffer(queue, bytemuck::cast_slice(&[self.bind_camera]));
 
-        render_pass.bind_camera(1, &self.raw_bind_camera);
-
-        for (_, bundle) in self.bundles.iter() {
-            render_pass.bind_raw(0, bundle);
+        {
+            let tri_attachments = [clear_attachment];
+            let mut render_pass = RenderPassBuilder::builder()
+                .label("voxel pass")
+                .color_attachments(&tri_attachments)
+                .depth_stencil_attachment(targets.depth_attachment(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: true,
+                }))
+                .build()
+                .begin_profiled(encoder, ctx.profiler(), "voxel pass");
+
+            render_pass.bind_camera(1, &self.raw_bind_camera);
+
+            for (_, bundle) in self.bundles.iter() {
+                render_pass.bind_raw(0, bundle);
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use engine::render::golden::GoldenImage;
+
+    use super::VoxelFramework;
+
+    #[test]
+    fn renders_like_the_reference() {
+        GoldenImage::builder()
+            .reference(concat!(env!("CARGO_MANIFEST_DIR"), "/golden/voxel.png"))
+            .frames(3)
+            .build()
+            .assert_framework::<VoxelFramework>();
+    }
+}